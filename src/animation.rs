@@ -0,0 +1,205 @@
+use std::fs;
+use std::io::{IsTerminal, Write};
+use std::path::PathBuf;
+use std::thread::sleep;
+use std::time::Duration;
+use crate::simulation::{Simulation, Simulator};
+
+#[derive(Copy, Clone, Eq, PartialEq)]
+pub enum Color {
+    Gray,
+    Red,
+    Green,
+    Yellow,
+    Blue,
+    Magenta,
+    Cyan,
+}
+
+impl Color {
+    pub const PALETTE: [Color; 6] = [Color::Red, Color::Green, Color::Yellow, Color::Blue, Color::Magenta, Color::Cyan];
+
+    pub fn ansi_code(&self) -> u8 {
+        match self {
+            Color::Gray => 90,
+            Color::Red => 91,
+            Color::Green => 92,
+            Color::Yellow => 93,
+            Color::Blue => 94,
+            Color::Magenta => 95,
+            Color::Cyan => 96,
+        }
+    }
+
+    pub fn css_name(&self) -> &'static str {
+        match self {
+            Color::Gray => "gray",
+            Color::Red => "red",
+            Color::Green => "green",
+            Color::Yellow => "goldenrod",
+            Color::Blue => "blue",
+            Color::Magenta => "magenta",
+            Color::Cyan => "darkcyan",
+        }
+    }
+}
+
+#[derive(Copy, Clone)]
+pub struct Cell {
+    pub symbol: char,
+    pub color: Option<Color>,
+}
+
+impl Cell {
+    pub fn plain(symbol: char) -> Cell {
+        Cell { symbol, color: None }
+    }
+
+    pub fn colored(symbol: char, color: Color) -> Cell {
+        Cell { symbol, color: Some(color) }
+    }
+}
+
+pub trait Frame {
+    fn render(&self) -> Vec<Vec<Cell>>;
+
+    // the (x, y) cell the viewport should scroll to, if the interesting part of the frame moves around
+    fn focus(&self) -> Option<(usize, usize)> {
+        None
+    }
+}
+
+pub struct AnimationOptions {
+    pub frames_per_second: u32,
+    pub max_frames: usize,
+    pub viewport: (usize, usize),
+    pub scroll: (usize, usize),
+    // dump every frame into this directory instead of drawing to the terminal
+    pub frames_directory: Option<PathBuf>,
+}
+
+impl Default for AnimationOptions {
+    fn default() -> Self {
+        AnimationOptions {
+            frames_per_second: 10,
+            max_frames: 200,
+            viewport: (80, 40),
+            scroll: (0, 0),
+            frames_directory: None,
+        }
+    }
+}
+
+impl AnimationOptions {
+    // --fps 10 --max-frames 200 --viewport 80x40 --scroll 0,0 --frames-dir frames
+    pub fn from_args() -> AnimationOptions {
+        let mut options = AnimationOptions::default();
+        let args = std::env::args().collect::<Vec<String>>();
+
+        for pair in args.windows(2) {
+            let value = &pair[1];
+
+            match pair[0].as_str() {
+                "--fps" => options.frames_per_second = value.parse().unwrap(),
+                "--max-frames" => options.max_frames = value.parse().unwrap(),
+                "--viewport" => options.viewport = parse_pair(value, 'x'),
+                "--scroll" => options.scroll = parse_pair(value, ','),
+                "--frames-dir" => options.frames_directory = Some(PathBuf::from(value)),
+                _ => {}
+            }
+        }
+
+        // without a terminal to draw on, fall back to writing the frames to files
+        if options.frames_directory.is_none() && !std::io::stdout().is_terminal() {
+            options.frames_directory = Some(PathBuf::from("frames"));
+        }
+
+        options
+    }
+}
+
+fn parse_pair(s: &str, separator: char) -> (usize, usize) {
+    let (a, b) = s.split_once(separator).unwrap();
+    (a.parse().unwrap(), b.parse().unwrap())
+}
+
+pub fn animate<S: Simulation + Frame>(initial_state: S, options: &AnimationOptions) -> std::io::Result<()> {
+    let mut simulator = Simulator::new(initial_state);
+
+    match &options.frames_directory {
+        None => animate_in_terminal(&mut simulator, options),
+        Some(directory) => dump_frames(&mut simulator, options, directory),
+    }
+}
+
+fn animate_in_terminal<S: Simulation + Frame>(simulator: &mut Simulator<S>, options: &AnimationOptions) -> std::io::Result<()> {
+    let mut stdout = std::io::stdout().lock();
+    let frame_duration = Duration::from_secs(1) / options.frames_per_second.max(1);
+
+    // hide the cursor and clear the screen once, afterwards every frame overwrites the previous one
+    write!(stdout, "\x1b[?25l\x1b[2J")?;
+
+    for _ in 0..options.max_frames {
+        let frame = simulator.state().render();
+        let (offset_x, offset_y) = viewport_offset(&frame, simulator.state().focus(), options);
+
+        write!(stdout, "\x1b[H")?;
+        writeln!(stdout, "step {} ({} events)\x1b[K", simulator.current_step(), simulator.events_of_step(simulator.current_step()).count())?;
+
+        for row in frame.iter().skip(offset_y).take(options.viewport.1) {
+            for cell in row.iter().skip(offset_x).take(options.viewport.0) {
+                match cell.color {
+                    None => write!(stdout, "{}", cell.symbol)?,
+                    Some(color) => write!(stdout, "\x1b[{}m{}\x1b[0m", color.ansi_code(), cell.symbol)?,
+                }
+            }
+
+            writeln!(stdout, "\x1b[K")?;
+        }
+
+        write!(stdout, "\x1b[J")?;
+        stdout.flush()?;
+
+        if !simulator.step() {
+            break;
+        }
+
+        sleep(frame_duration);
+    }
+
+    writeln!(stdout, "\x1b[?25h")
+}
+
+fn dump_frames<S: Simulation + Frame>(simulator: &mut Simulator<S>, options: &AnimationOptions, directory: &PathBuf) -> std::io::Result<()> {
+    fs::create_dir_all(directory)?;
+
+    for frame_index in 0..options.max_frames {
+        let text = simulator.state().render()
+            .iter()
+            .map(|row| row.iter().map(|cell| cell.symbol).collect::<String>() + "\n")
+            .collect::<String>();
+
+        fs::write(directory.join(format!("frame_{:05}.txt", frame_index)), text)?;
+
+        if !simulator.step() {
+            break;
+        }
+    }
+
+    Ok(())
+}
+
+fn viewport_offset(frame: &[Vec<Cell>], focus: Option<(usize, usize)>, options: &AnimationOptions) -> (usize, usize) {
+    let (viewport_width, viewport_height) = options.viewport;
+    let frame_width = frame.iter().map(|row| row.len()).max().unwrap_or(0);
+
+    let (x, y) = match focus {
+        None => options.scroll,
+        Some((x, y)) => (x.saturating_sub(viewport_width / 2), y.saturating_sub(viewport_height / 2)),
+    };
+
+    (
+        x.min(frame_width.saturating_sub(viewport_width)),
+        y.min(frame.len().saturating_sub(viewport_height)),
+    )
+}
@@ -0,0 +1,493 @@
+use std::cmp::Ordering;
+use std::fmt;
+use std::iter::Sum;
+use std::ops::{Add, Div, Mul, Neg, Rem, Sub};
+use std::str::FromStr;
+
+#[derive(Clone, Debug, PartialEq, Eq, Hash, Default)]
+pub struct BigInt {
+    negative: bool,
+    // little-endian base 2^32 limbs without trailing zeros (zero is the empty vector)
+    magnitude: Vec<u32>,
+}
+
+impl BigInt {
+    pub fn zero() -> BigInt {
+        BigInt::default()
+    }
+
+    pub fn one() -> BigInt {
+        BigInt::from(1u64)
+    }
+
+    pub fn is_zero(&self) -> bool {
+        self.magnitude.is_empty()
+    }
+
+    pub fn is_negative(&self) -> bool {
+        self.negative
+    }
+
+    pub fn abs(&self) -> BigInt {
+        BigInt { negative: false, magnitude: self.magnitude.clone() }
+    }
+
+    pub fn to_i64(&self) -> Option<i64> {
+        if self.magnitude.len() > 2 {
+            return None;
+        }
+
+        let magnitude = self.magnitude.iter().rev().fold(0u64, |acc, limb| (acc << 32) | *limb as u64);
+
+        if self.negative {
+            if magnitude > i64::MAX as u64 + 1 {
+                return None;
+            }
+
+            return Some((magnitude as i64).wrapping_neg());
+        }
+
+        i64::try_from(magnitude).ok()
+    }
+
+    pub fn gcd(&self, other: &BigInt) -> BigInt {
+        let mut a = self.abs();
+        let mut b = other.abs();
+
+        while !b.is_zero() {
+            let remainder = &a % &b;
+            a = b;
+            b = remainder;
+        }
+
+        a
+    }
+
+    // truncating division, the remainder has the sign of the dividend
+    pub fn div_rem(&self, divisor: &BigInt) -> (BigInt, BigInt) {
+        assert!(!divisor.is_zero(), "division by zero");
+
+        let (quotient, remainder) = div_rem_magnitude(&self.magnitude, &divisor.magnitude);
+
+        (
+            BigInt::from_parts(self.negative != divisor.negative, quotient),
+            BigInt::from_parts(self.negative, remainder),
+        )
+    }
+
+    // floor division, the remainder has the sign of the divisor
+    pub fn div_floor(&self, divisor: &BigInt) -> BigInt {
+        let (quotient, remainder) = self.div_rem(divisor);
+
+        if !remainder.is_zero() && remainder.negative != divisor.negative {
+            return quotient - BigInt::one();
+        }
+
+        quotient
+    }
+
+    // largest r with r * r <= self
+    pub fn sqrt(&self) -> BigInt {
+        assert!(!self.negative, "square root of a negative number");
+
+        if self.is_zero() {
+            return BigInt::zero();
+        }
+
+        let mut x = BigInt::one().shl(self.bits().div_ceil(2));
+
+        loop {
+            let y = (&x + &(self / &x)).shr(1);
+
+            if y >= x {
+                return x;
+            }
+
+            x = y;
+        }
+    }
+
+    fn bits(&self) -> usize {
+        match self.magnitude.last() {
+            None => 0,
+            Some(last) => self.magnitude.len() * 32 - last.leading_zeros() as usize,
+        }
+    }
+
+    fn shl(&self, bits: usize) -> BigInt {
+        BigInt::from_parts(self.negative, shl_magnitude(&self.magnitude, bits))
+    }
+
+    fn shr(&self, bits: usize) -> BigInt {
+        BigInt::from_parts(self.negative, shr_magnitude(&self.magnitude, bits))
+    }
+
+    fn from_parts(negative: bool, mut magnitude: Vec<u32>) -> BigInt {
+        while magnitude.last() == Some(&0) {
+            magnitude.pop();
+        }
+
+        BigInt { negative: negative && !magnitude.is_empty(), magnitude }
+    }
+}
+
+fn compare_magnitude(a: &[u32], b: &[u32]) -> Ordering {
+    a.len().cmp(&b.len())
+        .then_with(|| a.iter().rev().cmp(b.iter().rev()))
+}
+
+fn add_magnitude(a: &[u32], b: &[u32]) -> Vec<u32> {
+    let (longer, shorter) = if a.len() >= b.len() { (a, b) } else { (b, a) };
+
+    let mut result = Vec::with_capacity(longer.len() + 1);
+    let mut carry = 0u64;
+
+    for (i, limb) in longer.iter().enumerate() {
+        let sum = *limb as u64 + *shorter.get(i).unwrap_or(&0) as u64 + carry;
+        result.push(sum as u32);
+        carry = sum >> 32;
+    }
+
+    if carry > 0 {
+        result.push(carry as u32);
+    }
+
+    result
+}
+
+// requires a >= b
+fn sub_magnitude(a: &[u32], b: &[u32]) -> Vec<u32> {
+    let mut result = Vec::with_capacity(a.len());
+    let mut borrow = 0i64;
+
+    for (i, limb) in a.iter().enumerate() {
+        let mut difference = *limb as i64 - *b.get(i).unwrap_or(&0) as i64 - borrow;
+        borrow = 0;
+
+        if difference < 0 {
+            difference += 1 << 32;
+            borrow = 1;
+        }
+
+        result.push(difference as u32);
+    }
+
+    result
+}
+
+fn mul_magnitude(a: &[u32], b: &[u32]) -> Vec<u32> {
+    if a.is_empty() || b.is_empty() {
+        return Vec::new();
+    }
+
+    let mut result = vec![0u32; a.len() + b.len()];
+
+    for (i, x) in a.iter().enumerate() {
+        let mut carry = 0u64;
+
+        for (j, y) in b.iter().enumerate() {
+            let product = *x as u64 * *y as u64 + result[i + j] as u64 + carry;
+            result[i + j] = product as u32;
+            carry = product >> 32;
+        }
+
+        result[i + b.len()] = carry as u32;
+    }
+
+    result
+}
+
+fn shl_magnitude(a: &[u32], bits: usize) -> Vec<u32> {
+    if a.is_empty() {
+        return Vec::new();
+    }
+
+    let (limbs, bits) = (bits / 32, bits % 32);
+    let mut result = vec![0u32; limbs];
+
+    if bits == 0 {
+        result.extend_from_slice(a);
+        return result;
+    }
+
+    let mut carry = 0u32;
+    for limb in a {
+        result.push((limb << bits) | carry);
+        carry = limb >> (32 - bits);
+    }
+
+    if carry > 0 {
+        result.push(carry);
+    }
+
+    result
+}
+
+fn shr_magnitude(a: &[u32], bits: usize) -> Vec<u32> {
+    let (limbs, bits) = (bits / 32, bits % 32);
+
+    if limbs >= a.len() {
+        return Vec::new();
+    }
+
+    let a = &a[limbs..];
+
+    if bits == 0 {
+        return a.to_vec();
+    }
+
+    (0..a.len())
+        .map(|i| (a[i] >> bits) | a.get(i + 1).map_or(0, |next| next << (32 - bits)))
+        .collect()
+}
+
+// Knuth's algorithm D (long division in base 2^32), as in Hacker's Delight `divmnu`
+fn div_rem_magnitude(u: &[u32], v: &[u32]) -> (Vec<u32>, Vec<u32>) {
+    if compare_magnitude(u, v) == Ordering::Less {
+        return (Vec::new(), u.to_vec());
+    }
+
+    if v.len() == 1 {
+        let divisor = v[0] as u64;
+        let mut quotient = vec![0u32; u.len()];
+        let mut remainder = 0u64;
+
+        for i in (0..u.len()).rev() {
+            let current = (remainder << 32) | u[i] as u64;
+            quotient[i] = (current / divisor) as u32;
+            remainder = current % divisor;
+        }
+
+        return (quotient, vec![remainder as u32]);
+    }
+
+    let n = v.len();
+    let m = u.len() - n;
+    let shift = v[n - 1].leading_zeros() as usize;
+
+    let vn = shl_magnitude(v, shift);
+    let mut un = shl_magnitude(u, shift);
+    un.resize(u.len() + 1, 0);
+
+    let mut quotient = vec![0u32; m + 1];
+
+    for j in (0..=m).rev() {
+        let numerator = ((un[j + n] as u64) << 32) | un[j + n - 1] as u64;
+        let mut q_hat = numerator / vn[n - 1] as u64;
+        let mut r_hat = numerator % vn[n - 1] as u64;
+
+        while q_hat >= 1 << 32 || q_hat * vn[n - 2] as u64 > ((r_hat << 32) | un[j + n - 2] as u64) {
+            q_hat -= 1;
+            r_hat += vn[n - 1] as u64;
+
+            if r_hat >= 1 << 32 {
+                break;
+            }
+        }
+
+        let mut borrow = 0i64;
+        for i in 0..n {
+            let product = q_hat * vn[i] as u64;
+            let t = un[i + j] as i64 - borrow - (product & 0xFFFF_FFFF) as i64;
+            un[i + j] = t as u32;
+            borrow = (product >> 32) as i64 - (t >> 32);
+        }
+
+        let t = un[j + n] as i64 - borrow;
+        un[j + n] = t as u32;
+        quotient[j] = q_hat as u32;
+
+        // q_hat was one too large, add the divisor back
+        if t < 0 {
+            quotient[j] = quotient[j].wrapping_sub(1);
+
+            let mut carry = 0u64;
+            for i in 0..n {
+                let sum = un[i + j] as u64 + vn[i] as u64 + carry;
+                un[i + j] = sum as u32;
+                carry = sum >> 32;
+            }
+
+            un[j + n] = un[j + n].wrapping_add(carry as u32);
+        }
+    }
+
+    (quotient, shr_magnitude(&un[..n], shift))
+}
+
+impl From<u64> for BigInt {
+    fn from(value: u64) -> Self {
+        BigInt::from_parts(false, vec![value as u32, (value >> 32) as u32])
+    }
+}
+
+impl From<i64> for BigInt {
+    fn from(value: i64) -> Self {
+        let magnitude = BigInt::from(value.unsigned_abs());
+        BigInt::from_parts(value < 0, magnitude.magnitude)
+    }
+}
+
+impl From<i128> for BigInt {
+    fn from(value: i128) -> Self {
+        let magnitude = value.unsigned_abs();
+        BigInt::from_parts(value < 0, (0..4).map(|i| (magnitude >> (32 * i)) as u32).collect())
+    }
+}
+
+impl FromStr for BigInt {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (negative, digits) = match s.strip_prefix('-') {
+            Some(digits) => (true, digits),
+            None => (false, s.strip_prefix('+').unwrap_or(s)),
+        };
+
+        if digits.is_empty() || !digits.bytes().all(|digit| digit.is_ascii_digit()) {
+            return Err(());
+        }
+
+        // consume 9 decimal digits at a time, 10^9 fits into a single limb
+        let magnitude = digits.as_bytes().chunks(9)
+            .fold(BigInt::zero(), |acc, chunk| {
+                let value = std::str::from_utf8(chunk).unwrap().parse::<u64>().unwrap();
+                acc * BigInt::from(10u64.pow(chunk.len() as u32)) + BigInt::from(value)
+            });
+
+        Ok(BigInt::from_parts(negative, magnitude.magnitude))
+    }
+}
+
+impl fmt::Display for BigInt {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.is_zero() {
+            return write!(f, "0");
+        }
+
+        let billion = BigInt::from(1_000_000_000u64);
+        let mut chunks = Vec::new();
+        let mut rest = self.abs();
+
+        while !rest.is_zero() {
+            let (quotient, remainder) = rest.div_rem(&billion);
+            chunks.push(remainder.to_i64().unwrap());
+            rest = quotient;
+        }
+
+        let mut digits = chunks.pop().unwrap().to_string();
+        for chunk in chunks.iter().rev() {
+            digits += &format!("{:09}", chunk);
+        }
+
+        f.pad_integral(!self.negative, "", &digits)
+    }
+}
+
+impl Ord for BigInt {
+    fn cmp(&self, other: &Self) -> Ordering {
+        match (self.negative, other.negative) {
+            (false, true) => Ordering::Greater,
+            (true, false) => Ordering::Less,
+            (false, false) => compare_magnitude(&self.magnitude, &other.magnitude),
+            (true, true) => compare_magnitude(&other.magnitude, &self.magnitude),
+        }
+    }
+}
+
+impl PartialOrd for BigInt {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Neg for &BigInt {
+    type Output = BigInt;
+
+    fn neg(self) -> BigInt {
+        BigInt::from_parts(!self.negative, self.magnitude.clone())
+    }
+}
+
+impl Neg for BigInt {
+    type Output = BigInt;
+
+    fn neg(self) -> BigInt {
+        BigInt::from_parts(!self.negative, self.magnitude)
+    }
+}
+
+impl Add for &BigInt {
+    type Output = BigInt;
+
+    fn add(self, other: &BigInt) -> BigInt {
+        if self.negative == other.negative {
+            return BigInt::from_parts(self.negative, add_magnitude(&self.magnitude, &other.magnitude));
+        }
+
+        match compare_magnitude(&self.magnitude, &other.magnitude) {
+            Ordering::Less => BigInt::from_parts(other.negative, sub_magnitude(&other.magnitude, &self.magnitude)),
+            _ => BigInt::from_parts(self.negative, sub_magnitude(&self.magnitude, &other.magnitude)),
+        }
+    }
+}
+
+impl Sub for &BigInt {
+    type Output = BigInt;
+
+    fn sub(self, other: &BigInt) -> BigInt {
+        self + &(-other)
+    }
+}
+
+impl Mul for &BigInt {
+    type Output = BigInt;
+
+    fn mul(self, other: &BigInt) -> BigInt {
+        BigInt::from_parts(self.negative != other.negative, mul_magnitude(&self.magnitude, &other.magnitude))
+    }
+}
+
+impl Div for &BigInt {
+    type Output = BigInt;
+
+    fn div(self, other: &BigInt) -> BigInt {
+        self.div_rem(other).0
+    }
+}
+
+impl Rem for &BigInt {
+    type Output = BigInt;
+
+    fn rem(self, other: &BigInt) -> BigInt {
+        self.div_rem(other).1
+    }
+}
+
+macro_rules! forward_owned_binary_op {
+    ($($trait:ident $method:ident),*) => {$(
+        impl $trait for BigInt {
+            type Output = BigInt;
+
+            fn $method(self, other: BigInt) -> BigInt {
+                (&self).$method(&other)
+            }
+        }
+
+        impl $trait<&BigInt> for BigInt {
+            type Output = BigInt;
+
+            fn $method(self, other: &BigInt) -> BigInt {
+                (&self).$method(other)
+            }
+        }
+    )*};
+}
+
+forward_owned_binary_op!(Add add, Sub sub, Mul mul, Div div, Rem rem);
+
+impl Sum for BigInt {
+    fn sum<I: Iterator<Item=BigInt>>(iter: I) -> Self {
+        iter.fold(BigInt::zero(), |acc, value| acc + value)
+    }
+}
@@ -0,0 +1,170 @@
+use std::fmt::Debug;
+use std::hash::Hash;
+use std::ops::{BitAnd, BitOr, BitXor, Not, Shl, Shr};
+
+pub trait Word: Copy + Eq + Hash + Debug + Default
++ BitAnd<Output=Self> + BitOr<Output=Self> + BitXor<Output=Self> + Not<Output=Self>
++ Shl<usize, Output=Self> + Shr<usize, Output=Self> {
+    const BITS: usize;
+    const ZERO: Self;
+    const ONE: Self;
+
+    fn count_ones(self) -> u32;
+}
+
+macro_rules! impl_word {
+    ($($type:ty),*) => {$(
+        impl Word for $type {
+            const BITS: usize = <$type>::BITS as usize;
+            const ZERO: Self = 0;
+            const ONE: Self = 1;
+
+            fn count_ones(self) -> u32 {
+                <$type>::count_ones(self)
+            }
+        }
+    )*};
+}
+
+impl_word!(u64, u128);
+
+// a set of the indices 0..width, stored in N machine words without any allocation (bit i of word j
+// is index j * W::BITS + i), so it is Copy and the width is limited to N * W::BITS, bits at and above
+// the width are always zero
+#[derive(Copy, Clone, Eq, PartialEq, Hash, Debug)]
+pub struct BitSet<W: Word = u64, const N: usize = 1> {
+    words: [W; N],
+    width: usize,
+}
+
+impl<W: Word, const N: usize> BitSet<W, N> {
+    pub const CAPACITY: usize = N * W::BITS;
+
+    pub fn try_new(width: usize) -> Result<BitSet<W, N>, String> {
+        if width > Self::CAPACITY {
+            return Err(format!("a bitset of width {} does not fit into {} bits", width, Self::CAPACITY));
+        }
+
+        Ok(BitSet { words: [W::ZERO; N], width })
+    }
+
+    pub fn new(width: usize) -> BitSet<W, N> {
+        BitSet::try_new(width).unwrap()
+    }
+
+    pub fn try_from_bools<I: IntoIterator<Item=bool>>(bools: I) -> Result<BitSet<W, N>, String> {
+        let bools = bools.into_iter().collect::<Vec<bool>>();
+
+        Ok(bools.iter().enumerate()
+            .filter(|(_, bit)| **bit)
+            .fold(BitSet::try_new(bools.len())?, |set, (index, _)| set.with(index)))
+    }
+
+    pub fn from_bools<I: IntoIterator<Item=bool>>(bools: I) -> BitSet<W, N> {
+        BitSet::try_from_bools(bools).unwrap()
+    }
+
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn contains(&self, index: usize) -> bool {
+        index < self.width && self.words[index / W::BITS] & (W::ONE << (index % W::BITS)) != W::ZERO
+    }
+
+    pub fn with(&self, index: usize) -> BitSet<W, N> {
+        assert!(index < self.width, "index {} out of range for bitset of width {}", index, self.width);
+
+        let mut words = self.words;
+        words[index / W::BITS] = words[index / W::BITS] | (W::ONE << (index % W::BITS));
+
+        BitSet { words, width: self.width }
+    }
+
+    pub fn len(&self) -> usize {
+        self.words.iter().map(|word| word.count_ones() as usize).sum()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.words.iter().all(|word| *word == W::ZERO)
+    }
+
+    pub fn union(&self, other: &BitSet<W, N>) -> BitSet<W, N> {
+        self.combine(other, |a, b| a | b)
+    }
+
+    pub fn intersection(&self, other: &BitSet<W, N>) -> BitSet<W, N> {
+        self.combine(other, |a, b| a & b)
+    }
+
+    pub fn difference(&self, other: &BitSet<W, N>) -> BitSet<W, N> {
+        self.combine(other, |a, b| a & !b)
+    }
+
+    pub fn complement(&self) -> BitSet<W, N> {
+        BitSet { words: self.words.map(|word| !word), width: self.width }.masked()
+    }
+
+    // moves every index up by `amount`, dropping those that leave 0..width
+    pub fn shifted_up(&self, amount: usize) -> BitSet<W, N> {
+        if amount >= self.width {
+            return BitSet::new(self.width);
+        }
+
+        let (word_shift, bit_shift) = (amount / W::BITS, amount % W::BITS);
+
+        let words = std::array::from_fn(|index| match index.checked_sub(word_shift) {
+            None => W::ZERO,
+            Some(source) => match (bit_shift, source.checked_sub(1)) {
+                (0, _) => self.words[source],
+                (_, None) => self.words[source] << bit_shift,
+                (_, Some(carry)) => self.words[source] << bit_shift | self.words[carry] >> (W::BITS - bit_shift),
+            },
+        });
+
+        BitSet { words, width: self.width }.masked()
+    }
+
+    // moves every index down by `amount`, dropping those that would become negative
+    pub fn shifted_down(&self, amount: usize) -> BitSet<W, N> {
+        if amount >= self.width {
+            return BitSet::new(self.width);
+        }
+
+        let (word_shift, bit_shift) = (amount / W::BITS, amount % W::BITS);
+        let word = |index: usize| self.words.get(index).copied().unwrap_or(W::ZERO);
+
+        let words = std::array::from_fn(|index| match bit_shift {
+            0 => word(index + word_shift),
+            _ => word(index + word_shift) >> bit_shift | word(index + word_shift + 1) << (W::BITS - bit_shift),
+        });
+
+        BitSet { words, width: self.width }
+    }
+
+    // number of indices contained in exactly one of both sets (hamming distance)
+    pub fn xor_distance(&self, other: &BitSet<W, N>) -> usize {
+        self.combine(other, |a, b| a ^ b).len()
+    }
+
+    fn combine<F: Fn(W, W) -> W>(&self, other: &BitSet<W, N>, operation: F) -> BitSet<W, N> {
+        assert_eq!(self.width, other.width, "bitsets of different widths");
+
+        BitSet { words: std::array::from_fn(|index| operation(self.words[index], other.words[index])), width: self.width }
+    }
+
+    // clears the bits at and above the width
+    fn masked(mut self) -> BitSet<W, N> {
+        for (index, word) in self.words.iter_mut().enumerate() {
+            let used_bits = self.width.saturating_sub(index * W::BITS).min(W::BITS);
+
+            *word = match used_bits {
+                0 => W::ZERO,
+                _ if used_bits == W::BITS => *word,
+                _ => *word & !(!W::ZERO << used_bits),
+            };
+        }
+
+        self
+    }
+}
@@ -1,5 +1,7 @@
+use std::collections::{HashMap, VecDeque};
 use std::fs::File;
-use std::io::{BufRead, BufReader};
+use std::io::{BufRead, BufReader, Read};
+use std::time::Instant;
 
 #[allow(dead_code)]
 pub fn main() {
@@ -12,60 +14,284 @@ pub fn main() {
         .collect::<Vec<String>>();
 
     println!("PART 1: {}", part1(&lines));
-    println!("PART 2: {}", part2(&lines));
+    println!("PART 2: {}", part2(&lines, &lexicon_from_args()));
 }
 
-fn part1(lines: &Vec<String>) -> i32 {
+// prints every line together with the numbers found in it and their (character) positions
+#[allow(dead_code)]
+pub fn debug() {
+    let input_file = File::open("inputs/01.txt").unwrap();
+
+    let matcher = Matcher::new(&Lexicon::digits().merged_with(&lexicon_from_args()));
+
+    for line in BufReader::new(input_file).lines().map(|line| line.unwrap()) {
+        let numbers = matcher.find_numbers(&line);
+
+        let positions = numbers
+            .iter()
+            .map(|number| format!("{}@{}", number.value, number.position))
+            .collect::<Vec<String>>();
+
+        println!("{} => {:?} [{}]", line, calibration_value(&numbers), positions.join(", "));
+    }
+}
+
+// part 1 over an arbitrarily large document (--input <file>) in constant memory
+#[allow(dead_code)]
+pub fn stream() {
+    let args = std::env::args().collect::<Vec<String>>();
+    let path = match args.iter().position(|arg| arg == "--input") {
+        None => "inputs/01.txt",
+        Some(index) => args.get(index + 1).unwrap(),
+    };
+
+    let start = Instant::now();
+    let totals = calibrate_stream(File::open(path).unwrap()).unwrap();
+    let seconds = start.elapsed().as_secs_f64();
+
+    println!("PART 1: {}", totals.sum);
+    println!(
+        "{} lines, {} bytes in {:.3}s ({:.1} MB/s)",
+        totals.lines, totals.bytes, seconds, totals.bytes as f64 / 1_000_000.0 / seconds,
+    );
+}
+
+// --lexicon <file> replaces the english number words of part 2
+fn lexicon_from_args() -> Lexicon {
+    let args = std::env::args().collect::<Vec<String>>();
+
+    match args.iter().position(|arg| arg == "--lexicon") {
+        None => Lexicon::english(),
+        Some(index) => Lexicon::from_file(args.get(index + 1).unwrap()).unwrap(),
+    }
+}
+
+fn part1(lines: &Vec<String>) -> u32 {
     lines
         .iter()
         .map(|line| {
-            let numbers = line
-                .chars()
-                .filter(|char| char.is_ascii_digit())
-                .collect::<Vec<char>>();
+            let first = first_digit(line.as_bytes()).unwrap();
+            let last = last_digit(line.as_bytes()).unwrap();
 
-            return format!("{}{}", numbers[0], numbers[numbers.len() - 1]);
+            ((first - b'0') * 10 + (last - b'0')) as u32
         })
-        .map(|number_string| number_string.parse::<i32>().unwrap())
         .sum()
 }
 
-fn part2(lines: &Vec<String>) -> i32 {
+fn part2(lines: &Vec<String>, lexicon: &Lexicon) -> u32 {
+    let matcher = Matcher::new(&Lexicon::digits().merged_with(lexicon));
+
     lines
         .iter()
-        .map(|whole_line| {
-            let mut numbers: Vec<u32> = Vec::new();
-            let mut line = &whole_line[..];
-
-            while line.len() > 0 {
-                if line.starts_with(|c: char| c.is_ascii_digit()) {
-                    numbers.push(line.chars().next().unwrap().to_digit(10).unwrap());
-                    line = &line[1..];
-                    continue;
-                }
+        .map(|line| calibration_value(&matcher.find_numbers(line)).unwrap())
+        .sum()
+}
+
+// first digit of the first number and last digit of the last number, so that words
+// for multi-digit numbers ("twelve") still form a two-digit value
+fn calibration_value(numbers: &[FoundNumber]) -> Option<u32> {
+    let first = numbers.first()?.value.to_string();
+    let last = numbers.last()?.value.to_string();
+
+    Some(first.chars().next().unwrap().to_digit(10).unwrap() * 10 + last.chars().last().unwrap().to_digit(10).unwrap())
+}
+
+fn first_digit(bytes: &[u8]) -> Option<u8> {
+    bytes.iter().find(|byte| byte.is_ascii_digit()).copied()
+}
+
+fn last_digit(bytes: &[u8]) -> Option<u8> {
+    bytes.iter().rev().find(|byte| byte.is_ascii_digit()).copied()
+}
+
+struct CalibrationTotals {
+    sum: u64,
+    lines: u64,
+    bytes: u64,
+}
+
+// reads fixed-size chunks instead of whole lines, so a line may be split across chunks: the first
+// digit is kept from the first piece that has one, the last digit from the last piece that has one
+fn calibrate_stream<R: Read>(mut reader: R) -> std::io::Result<CalibrationTotals> {
+    let mut totals = CalibrationTotals { sum: 0, lines: 0, bytes: 0 };
+    let mut buffer = vec![0u8; 1 << 20];
+
+    let mut first = None;
+    let mut last = None;
+    let mut line_started = false;
+
+    loop {
+        let read_bytes = match reader.read(&mut buffer) {
+            Ok(0) => break,
+            Ok(read_bytes) => read_bytes,
+            Err(error) if error.kind() == std::io::ErrorKind::Interrupted => continue,
+            Err(error) => return Err(error),
+        };
+
+        totals.bytes += read_bytes as u64;
+
+        for piece in buffer[..read_bytes].split_inclusive(|byte| *byte == b'\n') {
+            let (content, line_ends) = match piece.split_last() {
+                Some((b'\n', content)) => (content, true),
+                _ => (piece, false),
+            };
+
+            line_started = true;
+
+            if first.is_none() {
+                first = first_digit(content);
+            }
+
+            if let Some(digit) = last_digit(content) {
+                last = Some(digit);
+            }
 
-                let number = match () {
-                    _ if line.starts_with("one") => 1,
-                    _ if line.starts_with("two") => 2,
-                    _ if line.starts_with("three") => 3,
-                    _ if line.starts_with("four") => 4,
-                    _ if line.starts_with("five") => 5,
-                    _ if line.starts_with("six") => 6,
-                    _ if line.starts_with("seven") => 7,
-                    _ if line.starts_with("eight") => 8,
-                    _ if line.starts_with("nine") => 9,
-                    _ => 0
+            if line_ends {
+                totals.add_line(first.take(), last.take());
+                line_started = false;
+            }
+        }
+    }
+
+    // the last line does not have to end with a line break
+    if line_started {
+        totals.add_line(first, last);
+    }
+
+    Ok(totals)
+}
+
+impl CalibrationTotals {
+    // lines without any digit count as a calibration value of 0
+    fn add_line(&mut self, first: Option<u8>, last: Option<u8>) {
+        if let (Some(first), Some(last)) = (first, last) {
+            self.sum += ((first - b'0') * 10 + (last - b'0')) as u64;
+        }
+
+        self.lines += 1;
+    }
+}
+
+struct Lexicon {
+    words: Vec<(String, u32)>,
+}
+
+impl Lexicon {
+    fn digits() -> Lexicon {
+        Lexicon { words: (0..=9).map(|digit| (digit.to_string(), digit)).collect() }
+    }
+
+    fn english() -> Lexicon {
+        let words = ["one", "two", "three", "four", "five", "six", "seven", "eight", "nine"];
+        Lexicon { words: (1..).zip(words).map(|(value, word)| (word.to_string(), value)).collect() }
+    }
+
+    // one "<word> <value>" pair per line, empty lines and lines starting with '#' are skipped
+    fn from_file(path: &str) -> Result<Lexicon, String> {
+        let content = std::fs::read_to_string(path).map_err(|error| format!("{}: {}", path, error))?;
+
+        let words = content.lines()
+            .enumerate()
+            .map(|(index, line)| (index, line.trim()))
+            .filter(|(_, line)| !line.is_empty() && !line.starts_with('#'))
+            .map(|(index, line)| {
+                let (word, value) = line.split_once(char::is_whitespace)
+                    .ok_or(format!("{}:{}: expected \"<word> <value>\"", path, index + 1))?;
+                let value = value.trim().parse::<u32>()
+                    .map_err(|_| format!("{}:{}: invalid value \"{}\"", path, index + 1, value.trim()))?;
+
+                Ok((word.to_string(), value))
+            })
+            .collect::<Result<Vec<(String, u32)>, String>>()?;
+
+        Ok(Lexicon { words })
+    }
+
+    fn merged_with(&self, other: &Lexicon) -> Lexicon {
+        Lexicon { words: self.words.iter().chain(other.words.iter()).cloned().collect() }
+    }
+}
+
+struct FoundNumber {
+    position: usize,
+    value: u32,
+}
+
+// aho-corasick automaton over all words of a lexicon, so a line is scanned once no matter how many
+// words there are, and overlapping words ("eightwo") are all reported
+struct Matcher {
+    transitions: Vec<HashMap<char, usize>>,
+    fallbacks: Vec<usize>,
+    // (word length in characters, value) of every word ending in this state
+    outputs: Vec<Vec<(usize, u32)>>,
+}
+
+impl Matcher {
+    fn new(lexicon: &Lexicon) -> Matcher {
+        let mut matcher = Matcher { transitions: vec![HashMap::new()], fallbacks: vec![0], outputs: vec![vec![]] };
+
+        for (word, value) in &lexicon.words {
+            let mut state = 0;
+
+            for c in word.chars() {
+                state = match matcher.transitions[state].get(&c) {
+                    Some(next_state) => *next_state,
+                    None => {
+                        matcher.transitions.push(HashMap::new());
+                        matcher.fallbacks.push(0);
+                        matcher.outputs.push(vec![]);
+
+                        let next_state = matcher.transitions.len() - 1;
+                        matcher.transitions[state].insert(c, next_state);
+                        next_state
+                    }
                 };
+            }
+
+            matcher.outputs[state].push((word.chars().count(), *value));
+        }
+
+        // breadth first, so the fallback of a state is always complete before its children need it
+        let mut queue = matcher.transitions[0].values().copied().collect::<VecDeque<usize>>();
 
-                if number > 0 {
-                    numbers.push(number);
+        while let Some(state) = queue.pop_front() {
+            for (c, next_state) in matcher.transitions[state].clone() {
+                let mut fallback = matcher.fallbacks[state];
+                while fallback != 0 && !matcher.transitions[fallback].contains_key(&c) {
+                    fallback = matcher.fallbacks[fallback];
                 }
 
-                line = &line[1..];
+                let fallback = *matcher.transitions[fallback].get(&c).unwrap_or(&0);
+                matcher.fallbacks[next_state] = fallback;
+
+                let inherited_outputs = matcher.outputs[fallback].clone();
+                matcher.outputs[next_state].extend(inherited_outputs);
+
+                queue.push_back(next_state);
             }
+        }
 
-            return format!("{}{}", numbers[0], numbers[numbers.len() - 1]);
-        })
-        .map(|number_string| number_string.parse::<i32>().unwrap())
-        .sum()
+        matcher
+    }
+
+    // all numbers in the line, ordered by the character position they start at
+    fn find_numbers(&self, line: &str) -> Vec<FoundNumber> {
+        let mut numbers = vec![];
+        let mut state = 0;
+
+        for (position, c) in line.chars().enumerate() {
+            while state != 0 && !self.transitions[state].contains_key(&c) {
+                state = self.fallbacks[state];
+            }
+
+            state = *self.transitions[state].get(&c).unwrap_or(&0);
+
+            for (length, value) in &self.outputs[state] {
+                numbers.push(FoundNumber { position: position + 1 - length, value: *value });
+            }
+        }
+
+        numbers.sort_by_key(|number| number.position);
+        numbers
+    }
 }
@@ -1,6 +1,8 @@
-use std::cmp::max;
+use std::collections::{BTreeMap, BTreeSet};
 use std::fs::File;
 use std::io::{BufRead, BufReader};
+use std::str::FromStr;
+use rayon::prelude::*;
 use regex::Regex;
 
 #[allow(dead_code)]
@@ -13,59 +15,356 @@ pub fn main() {
         .map(|line| line.unwrap())
         .collect::<Vec<String>>();
 
-    println!("PART 1: {}", part1(&lines));
-    println!("PART 2: {}", part2(&lines));
+    let games = parse_games(&lines).unwrap();
+
+    println!("PART 1: {}", part1(&games, &bag_from_args()));
+    println!("PART 2: {}", part2(&games));
 }
 
-fn part1(lines: &Vec<String>) -> i32 {
-    lines
+// maximum-likelihood estimate of the bag all games were played with, --max-cubes bounds the search per color
+#[allow(dead_code)]
+pub fn estimate() {
+    let input_file = File::open("inputs/02.txt").unwrap();
+
+    let lines = BufReader::new(input_file)
+        .lines()
+        .map(|line| line.unwrap())
+        .collect::<Vec<String>>();
+
+    let games = parse_games(&lines).unwrap();
+
+    let args = std::env::args().collect::<Vec<String>>();
+    let max_cubes = match args.iter().position(|arg| arg == "--max-cubes") {
+        None => 100,
+        Some(index) => args.get(index + 1).unwrap().parse::<u32>().unwrap(),
+    };
+
+    let model = DrawModel::new(games.iter());
+
+    // the search starts at the biggest draw of every color, which has to be within the bound
+    let most_drawn = model.draws.iter().flatten().copied().max().unwrap_or(0);
+    if most_drawn > max_cubes {
+        println!("--max-cubes has to be at least {}, the most cubes of a color seen in a single draw", most_drawn);
+        return;
+    }
+
+    let amounts = model.fit(max_cubes);
+
+    for (color_index, color) in model.colors.iter().enumerate() {
+        match model.confidence_interval(&amounts, color_index, max_cubes) {
+            None => println!("{}: {} (no 95% interval within {} cubes)", color, amounts[color_index], max_cubes),
+            Some((low, high)) => {
+                let open_end = if high == max_cubes { "+" } else { "" };
+                println!("{}: {} (95% interval {}..={}{})", color, amounts[color_index], low, high, open_end);
+            }
+        }
+    }
+
+    println!("IMPOSSIBLE GAMES: {:?}", impossible_games(&games, max_cubes));
+}
+
+// --bag red=12,green=13,blue=14 or --bag-file <file> (one "<color> <amount>" per line)
+fn bag_from_args() -> Bag {
+    let args = std::env::args().collect::<Vec<String>>();
+
+    for pair in args.windows(2) {
+        match pair[0].as_str() {
+            "--bag" => return pair[1].parse().unwrap(),
+            "--bag-file" => return Bag::from_file(&pair[1]).unwrap(),
+            _ => {}
+        }
+    }
+
+    Bag::from_str("red=12,green=13,blue=14").unwrap()
+}
+
+fn part1(games: &[Game], bag: &Bag) -> u32 {
+    games
         .iter()
-        .map(|line| get_max_cube_amount_per_color(line))
-        .filter_map(|(game_id, (max_r, max_g, max_b))| {
-            let is_game_possible = max_r <= 12 && max_g <= 13 && max_b <= 14;
-            return if is_game_possible { Some(game_id) } else { None };
-        })
+        .filter(|game| game.is_possible_with(bag))
+        .map(|game| game.id)
         .sum()
 }
 
-fn part2(lines: &Vec<String>) -> i32 {
+fn part2(games: &[Game]) -> u32 {
+    // a color missing from a game still counts, with a minimum of 0 cubes
+    let colors = games.iter()
+        .flat_map(|game| game.draws.iter())
+        .flat_map(|draw| draw.cubes.iter().map(|(color, _)| color.clone()))
+        .collect::<BTreeSet<String>>();
+
+    games
+        .iter()
+        .map(|game| game.minimum_bag().power(&colors))
+        .sum()
+}
+
+fn parse_games(lines: &[String]) -> Result<Vec<Game>, String> {
     lines
         .iter()
-        .map(|line| get_max_cube_amount_per_color(line))
-        .map(|(_, (max_r, max_g, max_b))| {
-            max_r * max_g * max_b
+        .enumerate()
+        .map(|(index, line)| line.parse::<Game>().map_err(|error| format!("line {}: {}", index + 1, error)))
+        .collect()
+}
+
+// a game is impossible if it could not have been played with the bag estimated from all other games
+fn impossible_games(games: &[Game], max_cubes: u32) -> Vec<u32> {
+    games
+        .par_iter()
+        .enumerate()
+        .filter(|(index, game)| {
+            let other_games = games.iter().enumerate()
+                .filter(|(other_index, _)| other_index != index)
+                .map(|(_, other_game)| other_game);
+
+            let model = DrawModel::new(other_games);
+            !game.is_possible_with(&model.bag(&model.fit(max_cubes)))
         })
-        .sum()
+        .map(|(_, game)| game.id)
+        .collect()
+}
+
+struct Game {
+    id: u32,
+    draws: Vec<Draw>,
+}
+
+// the cubes shown in a single draw, every color at most once
+struct Draw {
+    cubes: Vec<(String, u32)>,
+}
+
+impl Game {
+    fn is_possible_with(&self, bag: &Bag) -> bool {
+        self.draws
+            .iter()
+            .flat_map(|draw| draw.cubes.iter())
+            .all(|(color, amount)| *amount <= bag.amount(color))
+    }
+
+    // the smallest bag every draw of the game could have been taken from
+    fn minimum_bag(&self) -> Bag {
+        let mut cubes = BTreeMap::new();
+
+        for (color, amount) in self.draws.iter().flat_map(|draw| draw.cubes.iter()) {
+            let maximum = cubes.entry(color.clone()).or_insert(0);
+            *maximum = (*maximum).max(*amount);
+        }
+
+        Bag { cubes }
+    }
+}
+
+impl FromStr for Game {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let line_regex = Regex::new(r"^Game ([0-9]+):(.*)$").unwrap();
+
+        let groups = line_regex.captures(s).ok_or(format!("expected \"Game <id>: <draws>\", got \"{}\"", s))?;
+        let id = groups[1].parse::<u32>().map_err(|_| format!("invalid game id \"{}\"", &groups[1]))?;
+
+        let draws = groups[2]
+            .split(';')
+            .map(|draw| draw.parse::<Draw>())
+            .collect::<Result<Vec<Draw>, String>>()
+            .map_err(|error| format!("game {}: {}", id, error))?;
+
+        Ok(Game { id, draws })
+    }
+}
+
+impl FromStr for Draw {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut cubes: Vec<(String, u32)> = vec![];
+
+        for cube in s.split(',').map(|cube| cube.trim()) {
+            let (amount, color) = cube.split_once(' ')
+                .ok_or(format!("expected \"<amount> <color>\", got \"{}\"", cube))?;
+            let amount = amount.parse::<u32>().map_err(|_| format!("invalid amount \"{}\"", amount))?;
+            let color = color.trim();
+
+            if color.is_empty() || color.contains(char::is_whitespace) {
+                return Err(format!("invalid color \"{}\"", color));
+            }
+
+            if cubes.iter().any(|(other, _)| other == color) {
+                return Err(format!("color \"{}\" appears twice in draw \"{}\"", color, s.trim()));
+            }
+
+            cubes.push((color.to_string(), amount));
+        }
+
+        Ok(Draw { cubes })
+    }
+}
+
+// colors not in the bag have no cubes at all
+struct Bag {
+    cubes: BTreeMap<String, u32>,
+}
+
+impl Bag {
+    fn from_file(path: &str) -> Result<Bag, String> {
+        let content = std::fs::read_to_string(path).map_err(|error| format!("{}: {}", path, error))?;
+
+        let cubes = content.lines()
+            .enumerate()
+            .map(|(index, line)| (index, line.trim()))
+            .filter(|(_, line)| !line.is_empty() && !line.starts_with('#'))
+            .map(|(index, line)| {
+                let (color, amount) = line.split_once(char::is_whitespace)
+                    .ok_or(format!("{}:{}: expected \"<color> <amount>\"", path, index + 1))?;
+                let amount = amount.trim().parse::<u32>()
+                    .map_err(|_| format!("{}:{}: invalid amount \"{}\"", path, index + 1, amount.trim()))?;
+
+                Ok((color.to_string(), amount))
+            })
+            .collect::<Result<BTreeMap<String, u32>, String>>()?;
+
+        Ok(Bag { cubes })
+    }
+
+    fn amount(&self, color: &str) -> u32 {
+        *self.cubes.get(color).unwrap_or(&0)
+    }
+
+    fn power(&self, colors: &BTreeSet<String>) -> u32 {
+        colors.iter().map(|color| self.amount(color)).product()
+    }
+}
+
+// red=12,green=13,blue=14
+impl FromStr for Bag {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let cubes = s
+            .split(',')
+            .map(|cube| {
+                let (color, amount) = cube.split_once('=').ok_or(format!("expected \"<color>=<amount>\", got \"{}\"", cube))?;
+                let amount = amount.trim().parse::<u32>().map_err(|_| format!("invalid amount \"{}\"", amount))?;
+
+                Ok((color.trim().to_string(), amount))
+            })
+            .collect::<Result<BTreeMap<String, u32>, String>>()?;
+
+        Ok(Bag { cubes })
+    }
 }
 
-fn get_max_cube_amount_per_color(line: &str) -> (i32, (i32, i32, i32)) {
-    let line_regex = Regex::new(r"^Game ([0-9]+):(.*)$").unwrap();
-
-    let groups = line_regex.captures(line.as_ref()).unwrap();
-    let game_id = groups.get(1).unwrap().as_str().parse::<i32>().unwrap();
-
-    let max_cube_count_per_color = groups.get(2).unwrap().as_str()
-        .split(";")
-        .map(|cube_set| {
-            cube_set
-                .split(",")
-                .map(|cubes| cubes.trim())
-                .fold((0, 0, 0), |(r, g, b), cubes| {
-                    let split = cubes.split(" ").collect::<Vec<&str>>();
-                    let cube_amount = split[0].parse::<i32>().unwrap();
-                    let cube_color = split[1];
-
-                    match cube_color {
-                        "red" => (cube_amount, g, b),
-                        "green" => (r, cube_amount, b),
-                        "blue" => (r, g, cube_amount),
-                        _ => (r, g, b)
+// every draw takes cubes without replacement from the full bag, and puts them back afterwards,
+// so the shown amounts of a draw follow a multivariate hypergeometric distribution
+struct DrawModel {
+    colors: Vec<String>,
+    // amount per color (in the order of `colors`) of every draw
+    draws: Vec<Vec<u32>>,
+}
+
+impl DrawModel {
+    fn new<'a, I: Iterator<Item=&'a Game>>(games: I) -> DrawModel {
+        let draws = games.flat_map(|game| game.draws.iter()).collect::<Vec<&Draw>>();
+
+        let mut colors = draws.iter()
+            .flat_map(|draw| draw.cubes.iter().map(|(color, _)| color.clone()))
+            .collect::<Vec<String>>();
+        colors.sort();
+        colors.dedup();
+
+        let draws = draws.iter()
+            .map(|draw| colors.iter()
+                .map(|color| draw.cubes.iter().find(|(other, _)| other == color).map_or(0, |(_, amount)| *amount))
+                .collect()
+            )
+            .collect();
+
+        DrawModel { colors, draws }
+    }
+
+    fn log_likelihood(&self, amounts: &[u32]) -> f64 {
+        let total = amounts.iter().sum::<u32>();
+
+        self.draws
+            .iter()
+            .map(|draw| {
+                if draw.iter().zip(amounts).any(|(drawn, amount)| drawn > amount) {
+                    return f64::NEG_INFINITY;
+                }
+
+                let ways_to_draw = draw.iter().zip(amounts)
+                    .map(|(drawn, amount)| ln_binomial(*amount, *drawn))
+                    .sum::<f64>();
+
+                ways_to_draw - ln_binomial(total, draw.iter().sum())
+            })
+            .sum()
+    }
+
+    // pattern search from the smallest possible bag, changing a single color or all colors at once,
+    // halving the step whenever no change improves the likelihood anymore
+    fn fit(&self, max_cubes: u32) -> Vec<u32> {
+        let mut amounts = (0..self.colors.len())
+            .map(|color_index| self.draws.iter().map(|draw| draw[color_index]).max().unwrap())
+            .collect::<Vec<u32>>();
+        let mut best = self.log_likelihood(&amounts);
+
+        let mut moves = (0..self.colors.len()).map(Some).collect::<Vec<Option<usize>>>();
+        moves.push(None);
+
+        let mut step = (max_cubes / 4).max(1) as i32;
+        while step > 0 {
+            let mut improved = false;
+
+            for color_index in &moves {
+                for delta in [step, -step] {
+                    let candidate = amounts.iter()
+                        .enumerate()
+                        .map(|(index, amount)| match color_index.is_none() || *color_index == Some(index) {
+                            true => amount.saturating_add_signed(delta).min(max_cubes),
+                            false => *amount,
+                        })
+                        .collect::<Vec<u32>>();
+
+                    let likelihood = self.log_likelihood(&candidate);
+                    if likelihood > best + 1e-9 {
+                        amounts = candidate;
+                        best = likelihood;
+                        improved = true;
                     }
-                })
-        })
-        .fold((0, 0, 0), |(max_r, max_g, max_b), (r, g, b)| {
-            (max(max_r, r), max(max_g, g), max(max_b, b))
-        });
+                }
+            }
+
+            if !improved {
+                step /= 2;
+            }
+        }
+
+        amounts
+    }
+
+    // all amounts of one color (keeping the others at the estimate) not rejected by a likelihood ratio
+    // test at 95%, i.e. within 3.84 / 2 of the best log likelihood, none if the estimate is impossible
+    fn confidence_interval(&self, amounts: &[u32], color_index: usize, max_cubes: u32) -> Option<(u32, u32)> {
+        let best = self.log_likelihood(amounts);
+
+        let accepted = (0..=max_cubes)
+            .filter(|amount| {
+                let mut candidate = amounts.to_vec();
+                candidate[color_index] = *amount;
+                2.0 * (best - self.log_likelihood(&candidate)) <= 3.841
+            })
+            .collect::<Vec<u32>>();
+
+        Some((*accepted.first()?, *accepted.last()?))
+    }
+
+    fn bag(&self, amounts: &[u32]) -> Bag {
+        Bag { cubes: self.colors.iter().cloned().zip(amounts.iter().copied()).collect() }
+    }
+}
 
-    (game_id, max_cube_count_per_color)
+fn ln_binomial(n: u32, k: u32) -> f64 {
+    (0..k).map(|i| ((n - i) as f64).ln() - ((i + 1) as f64).ln()).sum()
 }
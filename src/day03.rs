@@ -1,8 +1,9 @@
 use regex::Regex;
-use std::cmp::{max, min};
 use std::collections::HashMap;
 use std::fs::File;
 use std::io::{BufRead, BufReader};
+use std::str::FromStr;
+use crate::animation::{Cell, Color};
 
 #[allow(dead_code)]
 pub fn main() {
@@ -17,40 +18,49 @@ pub fn main() {
     println!("PART 2: {}", part2(&lines));
 }
 
+// prints the schematic with part numbers, other numbers, gears and other symbols in different colors,
+// --html writes a html page instead of ansi escape codes
+#[allow(dead_code)]
+pub fn annotate() {
+    let input_file = File::open("inputs/03.txt").unwrap();
+
+    let lines = BufReader::new(input_file)
+        .lines()
+        .map(|line| line.unwrap())
+        .collect::<Vec<String>>();
+
+    let cells = annotate_schematic(&lines, &gear_rule_from_args());
+
+    match std::env::args().any(|arg| arg == "--html") {
+        true => print!("{}", to_html(&cells)),
+        false => print!("{}", to_ansi(&cells)),
+    }
+}
+
+// --gear-rule "<symbols>:<adjacent numbers>:<product|sum>", e.g. "*:2:product" for the puzzle rule
+fn gear_rule_from_args() -> GearRule {
+    let args = std::env::args().collect::<Vec<String>>();
+
+    match args.iter().position(|arg| arg == "--gear-rule") {
+        None => GearRule { symbols: vec!['*'], adjacent_numbers: 2, aggregation: Aggregation::PRODUCT },
+        Some(index) => args.get(index + 1).unwrap().parse().unwrap(),
+    }
+}
+
 fn part1(lines: &Vec<String>) -> i32 {
-    parse_numbers(lines)
-        .iter()
-        .map(|number| (number.value, get_chars_around_number(lines, number)))
-        .filter_map(|(number, surrounding_chars)| {
-            if surrounding_chars.iter().any(|(c, _)| !c.is_ascii_digit() && *c != '.') {
-                return Some(number);
-            }
-            return None;
-        })
+    let schematic = Schematic::new(lines);
+
+    (0..schematic.numbers.len())
+        .filter(|number_index| !schematic.symbols_adjacent_to_number(*number_index).is_empty())
+        .map(|number_index| schematic.numbers[number_index].value)
         .sum()
 }
 
-fn part2(lines: &Vec<String>) -> i32 {
-    parse_numbers(lines)
+fn part2(lines: &Vec<String>) -> i64 {
+    Schematic::new(lines)
+        .gears(&gear_rule_from_args())
         .iter()
-        .fold(HashMap::new(), |mut numbers_per_gear, number| {
-            get_chars_around_number(lines, &number)
-                .iter()
-                .filter_map(|(c, position)| {
-                    if *c == '*' {
-                        return Some(position.clone());
-                    }
-                    return None;
-                })
-                .for_each(|gear_position| {
-                    numbers_per_gear.entry(gear_position).or_insert(Vec::new()).push(number.value);
-                });
-
-            numbers_per_gear
-        })
-        .values()
-        .filter(|adjacent_numbers| adjacent_numbers.len() == 2)
-        .map(|adjacent_numbers| adjacent_numbers.iter().fold(1, |acc, n| acc * n))
+        .map(|(_, value)| value)
         .sum()
 }
 
@@ -97,24 +107,245 @@ struct NumberInfo {
     length: usize,
 }
 
-fn get_chars_around_number(
-    lines: &Vec<String>,
-    number: &NumberInfo,
-) -> Vec<(char, (usize, usize))> {
-    let inclusive_line_start_index = max(0, number.line_index as i32 - 1) as usize;
-    let exclusive_line_end_index = min(lines.len(), number.line_index + 2);
+const LEGEND: [(&str, Color); 4] = [
+    ("part number", Color::Green),
+    ("other number", Color::Red),
+    ("gear", Color::Yellow),
+    ("other symbol", Color::Cyan),
+];
 
-    let inclusive_char_start_index = max(0, number.start_index as i32 - 1) as usize;
-    let exclusive_char_end_index =
-        min(lines[number.line_index].len(), number.start_index + number.length + 1);
+fn annotate_schematic(lines: &Vec<String>, rule: &GearRule) -> Vec<Vec<Cell>> {
+    let schematic = Schematic::new(lines);
 
-    let mut chars: Vec<(char, (usize, usize))> = vec![];
+    let mut cells = lines
+        .iter()
+        .map(|line| line.chars().map(Cell::plain).collect())
+        .collect::<Vec<Vec<Cell>>>();
+
+    for symbol in &schematic.symbols {
+        cells[symbol.line_index][symbol.char_index].color = Some(Color::Cyan);
+    }
+
+    for (symbol_index, _) in schematic.gears(rule) {
+        let symbol = &schematic.symbols[symbol_index];
+        cells[symbol.line_index][symbol.char_index].color = Some(Color::Yellow);
+    }
+
+    for (number_index, number) in schematic.numbers.iter().enumerate() {
+        let color = match schematic.symbols_adjacent_to_number(number_index).is_empty() {
+            true => Color::Red,
+            false => Color::Green,
+        };
+
+        for cell in cells[number.line_index].iter_mut().skip(number.start_index).take(number.length) {
+            cell.color = Some(color);
+        }
+    }
+
+    cells
+}
 
-    for i in inclusive_line_start_index..exclusive_line_end_index {
-        for j in inclusive_char_start_index..exclusive_char_end_index {
-            chars.push((lines[i].chars().nth(j).unwrap(), (i, j)))
+// neighbouring cells of the same color, so a number is colored as a whole
+fn color_runs(row: &[Cell]) -> Vec<(Option<Color>, String)> {
+    let mut runs: Vec<(Option<Color>, String)> = vec![];
+
+    for cell in row {
+        match runs.last_mut() {
+            Some((color, text)) if *color == cell.color => text.push(cell.symbol),
+            _ => runs.push((cell.color, cell.symbol.to_string())),
         }
     }
 
-    return chars;
+    runs
+}
+
+fn to_ansi(cells: &[Vec<Cell>]) -> String {
+    let colored = |text: &str, color: Color| format!("\x1b[{}m{}\x1b[0m", color.ansi_code(), text);
+
+    let legend = LEGEND
+        .iter()
+        .map(|(name, color)| colored(name, *color))
+        .collect::<Vec<String>>()
+        .join("  ");
+
+    let schematic = cells
+        .iter()
+        .map(|row| color_runs(row)
+            .into_iter()
+            .map(|(color, text)| match color {
+                None => text,
+                Some(color) => colored(&text, color),
+            })
+            .collect::<String>() + "\n"
+        )
+        .collect::<String>();
+
+    legend + "\n\n" + &schematic
+}
+
+fn to_html(cells: &[Vec<Cell>]) -> String {
+    let colored = |text: &str, color: Color| format!("<span style=\"color: {}\">{}</span>", color.css_name(), text);
+
+    let escape = |symbol: char| match symbol {
+        '<' => "&lt;".to_string(),
+        '>' => "&gt;".to_string(),
+        '&' => "&amp;".to_string(),
+        _ => symbol.to_string(),
+    };
+
+    let legend = LEGEND
+        .iter()
+        .map(|(name, color)| colored(name, *color))
+        .collect::<Vec<String>>()
+        .join(" ");
+
+    let schematic = cells
+        .iter()
+        .map(|row| color_runs(row)
+            .into_iter()
+            .map(|(color, text)| {
+                let text = text.chars().map(escape).collect::<String>();
+
+                match color {
+                    None => text,
+                    Some(color) => colored(&text, color),
+                }
+            })
+            .collect::<String>() + "\n"
+        )
+        .collect::<String>();
+
+    format!(
+        "<!DOCTYPE html>\n<html>\n<head><meta charset=\"utf-8\"><title>Engine schematic</title></head>\n<body>\n<p>{}</p>\n<pre>\n{}</pre>\n</body>\n</html>\n",
+        legend, schematic,
+    )
+}
+
+struct SymbolInfo {
+    symbol: char,
+    line_index: usize,
+    char_index: usize,
+}
+
+// numbers and symbols indexed by every cell they cover, so neighbours are looked up instead of searched
+struct Schematic {
+    numbers: Vec<NumberInfo>,
+    symbols: Vec<SymbolInfo>,
+    number_at: HashMap<(usize, usize), usize>,
+    symbol_at: HashMap<(usize, usize), usize>,
+}
+
+impl Schematic {
+    fn new(lines: &Vec<String>) -> Schematic {
+        let numbers = parse_numbers(lines);
+
+        let symbols = lines
+            .iter()
+            .enumerate()
+            .flat_map(|(line_index, line)| line
+                .chars()
+                .enumerate()
+                .filter(|(_, c)| !c.is_ascii_digit() && *c != '.')
+                .map(move |(char_index, symbol)| SymbolInfo { symbol, line_index, char_index })
+            )
+            .collect::<Vec<SymbolInfo>>();
+
+        let number_at = numbers
+            .iter()
+            .enumerate()
+            .flat_map(|(number_index, number)| (number.start_index..number.start_index + number.length)
+                .map(move |char_index| ((number.line_index, char_index), number_index))
+            )
+            .collect();
+
+        let symbol_at = symbols
+            .iter()
+            .enumerate()
+            .map(|(symbol_index, symbol)| ((symbol.line_index, symbol.char_index), symbol_index))
+            .collect();
+
+        Schematic { numbers, symbols, number_at, symbol_at }
+    }
+
+    fn numbers_adjacent_to_symbol(&self, symbol_index: usize) -> Vec<usize> {
+        let symbol = &self.symbols[symbol_index];
+
+        let mut number_indices = neighbours(symbol.line_index, symbol.char_index, 1)
+            .filter_map(|position| self.number_at.get(&position).copied())
+            .collect::<Vec<usize>>();
+
+        // a number touching the symbol with several digits is still only one number
+        number_indices.sort();
+        number_indices.dedup();
+        number_indices
+    }
+
+    fn symbols_adjacent_to_number(&self, number_index: usize) -> Vec<usize> {
+        let number = &self.numbers[number_index];
+
+        neighbours(number.line_index, number.start_index, number.length)
+            .filter_map(|position| self.symbol_at.get(&position).copied())
+            .collect()
+    }
+
+    // (symbol index, aggregated value) of every symbol matching the rule
+    fn gears(&self, rule: &GearRule) -> Vec<(usize, i64)> {
+        (0..self.symbols.len())
+            .filter(|symbol_index| rule.symbols.contains(&self.symbols[*symbol_index].symbol))
+            .map(|symbol_index| (symbol_index, self.numbers_adjacent_to_symbol(symbol_index)))
+            .filter(|(_, number_indices)| number_indices.len() == rule.adjacent_numbers)
+            .map(|(symbol_index, number_indices)| {
+                let values = number_indices.iter().map(|number_index| self.numbers[*number_index].value as i64);
+
+                let value = match rule.aggregation {
+                    Aggregation::PRODUCT => values.product(),
+                    Aggregation::SUM => values.sum(),
+                };
+
+                (symbol_index, value)
+            })
+            .collect()
+    }
+}
+
+// all cells around the horizontal run of `length` cells starting at (line_index, char_index),
+// positions outside the schematic simply never match anything
+fn neighbours(line_index: usize, char_index: usize, length: usize) -> impl Iterator<Item=(usize, usize)> {
+    (line_index.saturating_sub(1)..=line_index + 1)
+        .flat_map(move |i| (char_index.saturating_sub(1)..=char_index + length).map(move |j| (i, j)))
+        .filter(move |(i, j)| *i != line_index || *j < char_index || *j >= char_index + length)
+}
+
+struct GearRule {
+    symbols: Vec<char>,
+    adjacent_numbers: usize,
+    aggregation: Aggregation,
+}
+
+enum Aggregation {
+    PRODUCT,
+    SUM,
+}
+
+impl FromStr for GearRule {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let parts = s.split(':').collect::<Vec<&str>>();
+        if parts.len() != 3 || parts[0].is_empty() {
+            return Err(());
+        }
+
+        let aggregation = match parts[2] {
+            "product" => Aggregation::PRODUCT,
+            "sum" => Aggregation::SUM,
+            _ => return Err(()),
+        };
+
+        Ok(GearRule {
+            symbols: parts[0].chars().collect(),
+            adjacent_numbers: parts[1].parse().map_err(|_| ())?,
+            aggregation,
+        })
+    }
 }
@@ -1,8 +1,9 @@
-use std::collections::{HashMap, HashSet};
+use std::collections::HashSet;
 use regex::{Regex};
 use std::fs::File;
 use std::io::{BufRead, BufReader};
 use std::str::FromStr;
+use crate::bigint::BigInt;
 
 #[allow(dead_code)]
 pub fn main() {
@@ -30,53 +31,70 @@ fn part1(lines: &Vec<String>) -> i32 {
         .sum()
 }
 
-fn part2(lines: &Vec<String>) -> u32 {
-    let scratchcards: HashMap<u32, Scratchcard> = lines
+fn part2(lines: &Vec<String>) -> u64 {
+    copy_table(&parse_scratchcards(lines))
         .iter()
-        .map(|line| Scratchcard::from_str(line).unwrap())
-        .fold(HashMap::new(), |mut scratchcards, scratchcard| {
-            scratchcards.insert(scratchcard.card_number, scratchcard);
-            scratchcards
-        });
+        .map(|(_, copies)| copies)
+        .sum()
+}
 
-    let mut cache: HashMap<u32, u32> = HashMap::new();
+// prints how many copies of every card end up being scratched
+#[allow(dead_code)]
+pub fn report() {
+    let input_file = File::open("inputs/04.txt").unwrap();
 
-    let total_scratchcards: u32 = scratchcards.values()
-        .map(|scratchcard| {
-            calculate_total_scratchcards(scratchcard.card_number, &scratchcards, &mut cache) + 1
-        })
-        .sum();
+    let lines = BufReader::new(input_file)
+        .lines()
+        .map(|line| line.unwrap())
+        .collect::<Vec<String>>();
+
+    let scratchcards = parse_scratchcards(&lines);
+    let copy_table = copy_table(&scratchcards);
+
+    println!("{:>8} {:>8} {:>12}", "card", "matches", "copies");
 
-    total_scratchcards
+    for (scratchcard, (card_number, copies)) in scratchcards.iter().zip(&copy_table) {
+        println!("{:>8} {:>8} {:>12}", card_number, scratchcard.match_count(), copies);
+    }
+
+    println!("{:>8} {:>8} {:>12}", "total", "", copy_table.iter().map(|(_, copies)| copies).sum::<u64>());
 }
 
-fn calculate_total_scratchcards(
-    card_number: u32,
-    scratchcard_lookup: &HashMap<u32, Scratchcard>,
-    cache: &mut HashMap<u32, u32>,
-) -> u32 {
-    let match_count = scratchcard_lookup.get(&card_number).unwrap().match_count() as u32;
-
-    let mut total: u32 = match_count;
-
-    for won_card_number in (card_number + 1)..(card_number + match_count + 1) {
-        total += match cache.get(&won_card_number) {
-            None => {
-                let won_card_total = calculate_total_scratchcards(won_card_number, scratchcard_lookup, cache);
-                cache.insert(won_card_number, won_card_total);
-                won_card_total
-            }
-            Some(won_card_total) => *won_card_total
+// ordered by card number, card numbers do not need to be contiguous
+fn parse_scratchcards(lines: &[String]) -> Vec<Scratchcard> {
+    let mut scratchcards = lines
+        .iter()
+        .map(|line| Scratchcard::from_str(line).unwrap())
+        .collect::<Vec<Scratchcard>>();
+
+    scratchcards.sort_by_key(|scratchcard| scratchcard.card_number);
+    scratchcards
+}
+
+// (card number, copies) of every card, a card with n matches wins one copy of each of the next n cards
+// in the table per copy of itself, so the copies of a card are final once all earlier cards are processed
+fn copy_table(scratchcards: &[Scratchcard]) -> Vec<(u32, u64)> {
+    let mut copies = vec![1u64; scratchcards.len()];
+
+    for (index, scratchcard) in scratchcards.iter().enumerate() {
+        let won_cards = (index + 1)..(index + 1 + scratchcard.match_count()).min(scratchcards.len());
+
+        for won_card in won_cards {
+            copies[won_card] += copies[index];
         }
     }
 
-    total
+    scratchcards
+        .iter()
+        .map(|scratchcard| scratchcard.card_number)
+        .zip(copies)
+        .collect()
 }
 
 struct Scratchcard {
     card_number: u32,
-    numbers: HashSet<u8>,
-    winning_numbers: HashSet<u8>,
+    numbers: HashSet<BigInt>,
+    winning_numbers: HashSet<BigInt>,
 }
 
 impl Scratchcard {
@@ -94,18 +112,18 @@ impl FromStr for Scratchcard {
 
         let card_number = groups.get(1).unwrap().as_str().parse::<u32>().unwrap();
 
-        let winning_numbers: HashSet<u8> = HashSet::from_iter(
+        let winning_numbers: HashSet<BigInt> = HashSet::from_iter(
             groups.get(2).unwrap().as_str()
                 .split(" ")
                 .filter(|item| item.len() > 0)
-                .map(|number| number.trim().parse::<u8>().unwrap())
+                .map(|number| number.trim().parse::<BigInt>().unwrap())
         );
 
-        let numbers: HashSet<u8> = HashSet::from_iter(
+        let numbers: HashSet<BigInt> = HashSet::from_iter(
             groups.get(3).unwrap().as_str()
                 .split(" ")
                 .filter(|item| item.len() > 0)
-                .map(|number| number.trim().parse::<u8>().unwrap())
+                .map(|number| number.trim().parse::<BigInt>().unwrap())
         );
 
         Ok(Scratchcard { card_number, numbers, winning_numbers })
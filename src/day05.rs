@@ -1,6 +1,8 @@
 use std::cmp::{max, min};
+use std::collections::{HashMap, VecDeque};
 use std::fs::File;
 use std::io::{BufRead, BufReader};
+use regex::Regex;
 
 #[allow(dead_code)]
 pub fn main() {
@@ -16,135 +18,146 @@ pub fn main() {
 }
 
 fn part1(lines: &Vec<String>) -> u64 {
-    let almanac = parse_almanac_from_lines(lines);
+    let almanac = parse_almanac_from_lines(lines).unwrap();
+    let seed_to_location = almanac.composed_mapping("seed", "location").unwrap();
 
     almanac.seeds.iter()
-        .map(|seed| almanac.map_seed_to_location(*seed))
+        .map(|seed| seed_to_location.map_number(*seed))
         .min()
         .unwrap()
 }
 
 fn part2(lines: &Vec<String>) -> u64 {
-    let almanac = parse_almanac_from_lines(lines);
-    let seed_ranges = almanac.get_seed_ranges();
+    let almanac = parse_almanac_from_lines(lines).unwrap();
+    let seed_to_location = almanac.composed_mapping("seed", "location").unwrap();
 
-    let mut seed_locations_ranges: Vec<MappingRange> = almanac.mappings.last().unwrap().ranges.to_vec();
-    seed_locations_ranges.sort_by_key(|range| range.destination_range_start);
+    almanac.get_seed_ranges()
+        .iter()
+        .flat_map(|seed_range| seed_to_location.map_range(seed_range))
+        .map(|location_range| location_range.start)
+        .min()
+        .unwrap()
+}
 
-    let mut lowest_seed_locations: Vec<u64> = Vec::new();
+// prints the seed-to-location function composed from all mappings
+#[allow(dead_code)]
+pub fn table() {
+    let input_file = File::open("inputs/05.txt").unwrap();
 
-    for seed_location_range in &seed_locations_ranges[..seed_locations_ranges.len() - 1] {
-        let inputs = almanac.build_input_for_seed_location_range(*seed_location_range);
+    let lines = BufReader::new(input_file)
+        .lines()
+        .map(|line| line.unwrap())
+        .collect::<Vec<String>>();
 
-        for input in &inputs {
-            let matching_seed_ranges = seed_ranges.iter()
-                .filter(|seed_range| input.source_range_start <= seed_range.start + seed_range.length && seed_range.start <= input.source_range_start + input.range_length)
-                .map(|seed_range| {
-                    let start = max(seed_range.start, input.source_range_start);
-                    let end = min(seed_range.start + seed_range.length, input.source_range_start + input.range_length);
-                    SeedRange { start, length: end - start }
-                })
-                .collect::<Vec<SeedRange>>();
+    let seed_to_location = parse_almanac_from_lines(&lines).unwrap().composed_mapping("seed", "location").unwrap();
 
-            for matching_seed_range in matching_seed_ranges {
-                lowest_seed_locations.push(almanac.map_seed_to_location(matching_seed_range.start));
-            }
-        }
-    }
+    println!("{:>20} {:>20} {:>20}", "seeds from", "seeds to", "locations from");
 
-    *lowest_seed_locations.iter().min().unwrap()
+    for range in &seed_to_location.ranges {
+        println!(
+            "{:>20} {:>20} {:>20}",
+            range.source_range_start,
+            range.source_range_start + (range.range_length - 1),
+            range.destination_range_start,
+        );
+    }
 }
 
-fn parse_almanac_from_lines(lines: &Vec<String>) -> Almanac {
-    let seeds = (&lines.get(0).unwrap()[7..])
-        .split(" ")
-        .map(|number| number.parse::<u64>().unwrap())
-        .collect::<Vec<_>>();
-
-    let mapping_start_indices = vec![
-        lines.iter().position(|line| line == "seed-to-soil map:").unwrap(),
-        lines.iter().position(|line| line == "soil-to-fertilizer map:").unwrap(),
-        lines.iter().position(|line| line == "fertilizer-to-water map:").unwrap(),
-        lines.iter().position(|line| line == "water-to-light map:").unwrap(),
-        lines.iter().position(|line| line == "light-to-temperature map:").unwrap(),
-        lines.iter().position(|line| line == "temperature-to-humidity map:").unwrap(),
-        lines.iter().position(|line| line == "humidity-to-location map:").unwrap(),
-    ];
+// maps a value (or --length values) from one category to any other one, mappings are followed backwards
+// where needed, e.g. --from location --to seed --value 42 lists the seeds ending up at location 42
+#[allow(dead_code)]
+pub fn lookup() {
+    let input_file = File::open("inputs/05.txt").unwrap();
 
+    let lines = BufReader::new(input_file)
+        .lines()
+        .map(|line| line.unwrap())
+        .collect::<Vec<String>>();
 
-    let mut mappings: Vec<Mapping> = vec![];
-    for (i, start_index) in mapping_start_indices.iter().enumerate() {
-        let mut ranges: Vec<MappingRange> = vec![];
+    let almanac = parse_almanac_from_lines(&lines).unwrap();
 
-        let end_index = if i + 1 < mapping_start_indices.len() {
-            mapping_start_indices[i + 1] - 1
-        } else {
-            lines.len()
-        };
+    let args = std::env::args().collect::<Vec<String>>();
+    let arg = |name: &str| args.iter().position(|arg| arg == name).map(|index| args.get(index + 1).unwrap().as_str());
 
-        for line in &lines[(*start_index + 1)..end_index] {
-            let numbers = line
-                .split(" ")
-                .map(|number| number.parse::<u64>().unwrap())
-                .collect::<Vec<_>>();
+    let from = arg("--from").unwrap_or("seed");
+    let to = arg("--to").unwrap_or("location");
+    let range = ValueRange {
+        start: arg("--value").unwrap().parse().unwrap(),
+        length: arg("--length").map_or(1, |length| length.parse().unwrap()),
+    };
 
-            ranges.push(MappingRange {
-                destination_range_start: *numbers.get(0).unwrap(),
-                source_range_start: *numbers.get(1).unwrap(),
-                range_length: *numbers.get(2).unwrap(),
-            })
+    match almanac.map_ranges(from, to, vec![range]) {
+        Err(error) => println!("{}", error),
+        Ok(ranges) => {
+            for range in ranges {
+                println!("{} {}..={}", to, range.start, range.start + (range.length - 1));
+            }
         }
+    }
+}
 
-        ranges.sort_by_key(|range| range.source_range_start);
+fn parse_almanac_from_lines(lines: &Vec<String>) -> Result<Almanac, String> {
+    let header_regex = Regex::new(r"^([a-z]+)-to-([a-z]+) map:$").unwrap();
 
-        // fill empty ranges
-        let first_range = ranges.first().unwrap();
-        if first_range.source_range_start > 0 {
-            ranges.insert(0, MappingRange {
-                source_range_start: 0,
-                destination_range_start: 0,
-                range_length: first_range.source_range_start,
-            })
-        }
+    let seeds = lines.first()
+        .and_then(|line| line.strip_prefix("seeds:"))
+        .ok_or("line 1: expected \"seeds: <numbers>\"")?
+        .split_whitespace()
+        .map(|number| number.parse::<u64>().map_err(|_| format!("line 1: invalid seed \"{}\"", number)))
+        .collect::<Result<Vec<u64>, String>>()?;
 
-        let last_range = ranges.last().unwrap();
-        ranges.push(MappingRange {
-            source_range_start: last_range.source_range_start + last_range.range_length,
-            destination_range_start: last_range.source_range_start + last_range.range_length,
-            range_length: u64::MAX - (last_range.source_range_start + last_range.range_length),
-        });
-
-        for j in 1..(ranges.len() - 1) {
-            let current_range = ranges.get(j).unwrap();
-            let next_range = ranges.get(j + 1).unwrap();
+    let mut mappings: Vec<Mapping> = vec![];
+    let mut current_mapping: Option<(String, String, Vec<MappingRange>)> = None;
 
-            let intermediary_range_start = current_range.source_range_start + current_range.range_length;
-            let intermediary_range_length = next_range.source_range_start - intermediary_range_start;
+    for (index, line) in lines.iter().enumerate().skip(1) {
+        if line.trim().is_empty() {
+            continue;
+        }
 
-            if intermediary_range_length == 0 {
-                continue;
+        if let Some(groups) = header_regex.captures(line) {
+            if let Some((source, destination, ranges)) = current_mapping.take() {
+                mappings.push(Mapping::new(source, destination, ranges));
             }
 
-            ranges.insert(j + 1, MappingRange {
-                source_range_start: intermediary_range_start,
-                destination_range_start: intermediary_range_start,
-                range_length: intermediary_range_length,
-            });
+            current_mapping = Some((groups[1].to_string(), groups[2].to_string(), vec![]));
+            continue;
         }
 
-        mappings.push(Mapping { ranges });
+        let (_, _, ranges) = current_mapping
+            .as_mut()
+            .ok_or(format!("line {}: expected \"<source>-to-<destination> map:\"", index + 1))?;
+
+        let numbers = line
+            .split_whitespace()
+            .map(|number| number.parse::<u64>())
+            .collect::<Result<Vec<u64>, _>>()
+            .ok()
+            .filter(|numbers| numbers.len() == 3)
+            .ok_or(format!("line {}: expected \"<destination start> <source start> <length>\"", index + 1))?;
+
+        ranges.push(MappingRange {
+            destination_range_start: numbers[0],
+            source_range_start: numbers[1],
+            range_length: numbers[2],
+        });
     }
 
-    Almanac { seeds, mappings }
-}
+    if let Some((source, destination, ranges)) = current_mapping {
+        mappings.push(Mapping::new(source, destination, ranges));
+    }
 
+    Ok(Almanac { seeds, mappings })
+}
 
 struct Almanac {
     seeds: Vec<u64>,
     mappings: Vec<Mapping>,
 }
 
+#[derive(Clone)]
 struct Mapping {
+    source: String,
+    destination: String,
     ranges: Vec<MappingRange>,
 }
 
@@ -156,37 +169,85 @@ struct MappingRange {
 }
 
 #[derive(Copy, Clone)]
-struct SeedRange {
+struct ValueRange {
     start: u64,
     length: u64,
 }
 
 impl Almanac {
-    fn map_seed_to_location(&self, seed: u64) -> u64 {
-        let mut number = seed;
+    // the mappings leading from one category to the other, and whether each of them is applied
+    // forwards (true) or inverted (false), the categories form a graph that is searched breadth first
+    fn path(&self, from: &str, to: &str) -> Result<Vec<(&Mapping, bool)>, String> {
+        let mut previous: HashMap<&str, (&str, &Mapping, bool)> = HashMap::new();
+        let mut queue = VecDeque::from([from]);
+
+        while let Some(category) = queue.pop_front() {
+            if category == to {
+                break;
+            }
 
-        for mapping in &self.mappings {
-            number = mapping.map_number(number)
+            for mapping in &self.mappings {
+                let (next_category, forward) = match () {
+                    _ if mapping.source == category => (mapping.destination.as_str(), true),
+                    _ if mapping.destination == category => (mapping.source.as_str(), false),
+                    _ => continue,
+                };
+
+                if next_category != from && !previous.contains_key(next_category) {
+                    previous.insert(next_category, (category, mapping, forward));
+                    queue.push_back(next_category);
+                }
+            }
+        }
+
+        if from != to && !previous.contains_key(to) {
+            return Err(format!("no mappings lead from {} to {}", from, to));
         }
 
-        number
+        let mut path = vec![];
+        let mut category = to;
+
+        while category != from {
+            let (previous_category, mapping, forward) = previous[category];
+            path.push((mapping, forward));
+            category = previous_category;
+        }
+
+        path.reverse();
+        Ok(path)
+    }
+
+    fn map_ranges(&self, from: &str, to: &str, ranges: Vec<ValueRange>) -> Result<Vec<ValueRange>, String> {
+        let path = self.path(from, to)?;
+
+        Ok(path.iter().fold(ranges, |ranges, (mapping, forward)| {
+            ranges
+                .iter()
+                .flat_map(|range| match forward {
+                    true => mapping.map_range(range),
+                    false => mapping.preimage(range),
+                })
+                .collect()
+        }))
     }
 
-    fn build_input_for_seed_location_range(&self, seed_location_range: MappingRange) -> Vec<MappingRange> {
-        let mut inputs: Vec<MappingRange> = vec![seed_location_range];
+    // a single mapping doing the work of all mappings from one category to the other, which needs
+    // all of them to point towards the target category
+    fn composed_mapping(&self, from: &str, to: &str) -> Result<Mapping, String> {
+        let path = self.path(from, to)?;
 
-        for mapping in self.mappings.iter().rev() {
-            inputs = mapping.build_input_for_outputs(&inputs);
+        if path.is_empty() || path.iter().any(|(_, forward)| !forward) {
+            return Err(format!("{} is not mapped to {} directly", from, to));
         }
 
-        inputs
+        Ok(path.iter().skip(1).fold(path[0].0.clone(), |composed, (mapping, _)| composed.then(mapping)))
     }
 
-    fn get_seed_ranges(&self) -> Vec<SeedRange> {
-        let mut seed_ranges: Vec<SeedRange> = vec![];
+    fn get_seed_ranges(&self) -> Vec<ValueRange> {
+        let mut seed_ranges: Vec<ValueRange> = vec![];
 
         for i in (0..self.seeds.len()).step_by(2) {
-            seed_ranges.push(SeedRange {
+            seed_ranges.push(ValueRange {
                 start: *self.seeds.get(i).unwrap(),
                 length: *self.seeds.get(i + 1).unwrap(),
             });
@@ -197,6 +258,36 @@ impl Almanac {
 }
 
 impl Mapping {
+    // numbers not in any of the given ranges map to themselves, so identity ranges are added
+    // for the gaps, which makes the ranges cover all numbers
+    fn new(source: String, destination: String, mut given_ranges: Vec<MappingRange>) -> Mapping {
+        given_ranges.sort_by_key(|range| range.source_range_start);
+
+        let identity = |start: u64, end: u64| MappingRange {
+            source_range_start: start,
+            destination_range_start: start,
+            range_length: end - start,
+        };
+
+        let mut ranges = vec![];
+        let mut covered_until = 0;
+
+        for range in given_ranges {
+            if range.source_range_start > covered_until {
+                ranges.push(identity(covered_until, range.source_range_start));
+            }
+
+            covered_until = max(covered_until, range.source_range_start + range.range_length);
+            ranges.push(range);
+        }
+
+        if covered_until < u64::MAX {
+            ranges.push(identity(covered_until, u64::MAX));
+        }
+
+        Mapping { source, destination, ranges }
+    }
+
     fn map_number(&self, number: u64) -> u64 {
         for range in &self.ranges {
             if number >= range.source_range_start && number < range.source_range_start + range.range_length {
@@ -207,45 +298,67 @@ impl Mapping {
         return number;
     }
 
-    fn build_input_for_outputs(&self, output_ranges: &Vec<MappingRange>) -> Vec<MappingRange> {
-        let mut input_ranges: Vec<MappingRange> = vec![];
+    // the parts of the range are mapped one by one, since every mapping range has its own offset,
+    // the ranges are sorted by source, so the parts come out in the order of the input range
+    fn map_range(&self, range: &ValueRange) -> Vec<ValueRange> {
+        let range_end = range.start + range.length;
 
-        for output_range in output_ranges {
-            input_ranges.append(&mut self.build_input_for_output(output_range));
-        }
+        self.ranges
+            .iter()
+            .filter(|r| r.source_range_start < range_end && range.start < r.source_range_start + r.range_length)
+            .map(|r| {
+                let start = max(range.start, r.source_range_start);
+                let end = min(range_end, r.source_range_start + r.range_length);
 
-        return input_ranges;
+                ValueRange { start: r.destination_range_start + (start - r.source_range_start), length: end - start }
+            })
+            .collect()
     }
 
-    fn build_input_for_output(&self, output_range: &MappingRange) -> Vec<MappingRange> {
-        let output_range_start = output_range.source_range_start;
-        let output_range_end = output_range_start + output_range.range_length;
-
-        let mut input_ranges: Vec<MappingRange> = vec![];
+    // all numbers mapped into the range, there can be none or several parts, since mappings do not
+    // have to be one to one
+    fn preimage(&self, range: &ValueRange) -> Vec<ValueRange> {
+        let range_end = range.start + range.length;
 
-        let mut current_start = output_range_start;
+        let mut preimage = self.ranges
+            .iter()
+            .filter(|r| r.destination_range_start < range_end && range.start < r.destination_range_start + r.range_length)
+            .map(|r| {
+                let start = max(range.start, r.destination_range_start);
+                let end = min(range_end, r.destination_range_start + r.range_length);
 
-        while current_start < output_range_end {
-            let range = &self.ranges.iter()
-                .find(|r| r.destination_range_start <= current_start && r.destination_range_start + r.range_length > current_start)
-                .unwrap();
-
-            let non_intersecting_range_length = current_start - range.destination_range_start;
-            let mut intersection_range_length = range.range_length - non_intersecting_range_length;
-
-            if current_start + intersection_range_length > output_range_end {
-                intersection_range_length = output_range_end - current_start;
-            }
+                ValueRange { start: r.source_range_start + (start - r.destination_range_start), length: end - start }
+            })
+            .collect::<Vec<ValueRange>>();
 
-            input_ranges.push(MappingRange {
-                source_range_start: range.source_range_start + non_intersecting_range_length,
-                destination_range_start: range.destination_range_start + non_intersecting_range_length,
-                range_length: intersection_range_length,
-            });
+        preimage.sort_by_key(|range| range.start);
+        preimage
+    }
 
-            current_start += intersection_range_length;
-        }
+    // a mapping doing the same as first applying this mapping and then the next one, both mappings
+    // cover all numbers, so every range of this mapping just splits into the ranges it is mapped onto
+    fn then(&self, next: &Mapping) -> Mapping {
+        let mut ranges = self.ranges
+            .iter()
+            .flat_map(|range| {
+                let mut source_range_start = range.source_range_start;
+
+                next.map_range(&ValueRange { start: range.destination_range_start, length: range.range_length })
+                    .into_iter()
+                    .map(move |destination_range| {
+                        let piece = MappingRange {
+                            source_range_start,
+                            destination_range_start: destination_range.start,
+                            range_length: destination_range.length,
+                        };
+
+                        source_range_start += destination_range.length;
+                        piece
+                    })
+            })
+            .collect::<Vec<MappingRange>>();
 
-        input_ranges
+        ranges.sort_by_key(|range| range.source_range_start);
+        Mapping { source: self.source.clone(), destination: next.destination.clone(), ranges }
     }
 }
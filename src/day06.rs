@@ -1,6 +1,7 @@
 use std::fs::File;
 use std::io::{BufRead, BufReader};
 use std::iter::zip;
+use crate::bigint::BigInt;
 
 #[allow(dead_code)]
 pub fn main() {
@@ -15,49 +16,268 @@ pub fn main() {
     println!("PART 2: {}", part2(&lines));
 }
 
-fn part1(lines: &Vec<String>) -> i64 {
-    let times = lines.get(0).unwrap()[10..].split(" ")
-        .map(|number| number.trim()).filter(|number| !number.is_empty())
-        .map(|number| number.parse::<i64>().unwrap());
+fn part1(lines: &Vec<String>) -> BigInt {
+    let times = lines.first().unwrap()[10..].split_whitespace()
+        .map(|number| number.parse::<BigInt>().unwrap());
 
-    let distances = lines.get(1).unwrap()[10..].split(" ")
-        .map(|number| number.trim()).filter(|number| !number.is_empty())
-        .map(|number| number.parse::<i64>().unwrap());
+    let distances = lines.get(1).unwrap()[10..].split_whitespace()
+        .map(|number| number.parse::<BigInt>().unwrap());
 
     zip(times, distances)
         .map(|(time, distance)| Race { time, distance })
         .map(|race| race.calculate_number_of_ways_to_win())
-        .fold(1, |acc, n| acc * n)
+        .fold(BigInt::one(), |acc, n| acc * n)
 }
 
-fn part2(lines: &Vec<String>) -> i64 {
-    let time = lines.get(0).unwrap()[10..].replace(" ", "").parse::<i64>().unwrap();
-    let distance = lines.get(1).unwrap()[10..].replace(" ", "").parse::<i64>().unwrap();
+// the concatenated numbers can get arbitrarily long, so they are not limited to i64
+fn part2(lines: &Vec<String>) -> BigInt {
+    let time = lines.first().unwrap()[10..].replace(' ', "").parse::<BigInt>().unwrap();
+    let distance = lines.get(1).unwrap()[10..].replace(' ', "").parse::<BigInt>().unwrap();
     Race { time, distance }.calculate_number_of_ways_to_win()
 }
 
+// part 1 and 2 with a different way of charging the boat:
+// --model linear|quadratic|capped:<max speed>|decay:<speed lost per millisecond>
+#[allow(dead_code)]
+pub fn explore() {
+    let input_file = File::open("inputs/06.txt").unwrap();
+
+    let lines = BufReader::new(input_file)
+        .lines()
+        .map(|line| line.unwrap())
+        .collect::<Vec<String>>();
+
+    let model = charging_model_from_args();
+
+    let times = lines.first().unwrap()[10..].split_whitespace().map(|number| number.parse::<BigInt>().unwrap());
+    let distances = lines.get(1).unwrap()[10..].split_whitespace().map(|number| number.parse::<BigInt>().unwrap());
+
+    let product = zip(times, distances)
+        .map(|(time, distance)| Race { time, distance }.count_winning_hold_times(model.as_ref()))
+        .fold(BigInt::one(), |acc, n| acc * n);
+
+    let time = lines.first().unwrap()[10..].replace(' ', "").parse::<BigInt>().unwrap();
+    let distance = lines.get(1).unwrap()[10..].replace(' ', "").parse::<BigInt>().unwrap();
+
+    println!("PART 1: {}", product);
+    println!("PART 2: {}", Race { time, distance }.count_winning_hold_times(model.as_ref()));
+}
+
+fn charging_model_from_args() -> Box<dyn ChargingModel> {
+    let args = std::env::args().collect::<Vec<String>>();
+
+    let model = match args.iter().position(|arg| arg == "--model") {
+        None => "linear",
+        Some(index) => args.get(index + 1).unwrap().as_str(),
+    };
+
+    match model.split_once(':') {
+        None if model == "linear" => Box::new(Linear),
+        None if model == "quadratic" => Box::new(QuadraticAcceleration),
+        Some(("capped", max_speed)) => Box::new(CappedSpeed { max_speed: max_speed.parse().unwrap() }),
+        Some(("decay", decay)) => {
+            let decay = decay.parse::<BigInt>().unwrap();
+            assert!(decay > BigInt::zero(), "the decay has to be positive, use the linear model for no decay");
+            Box::new(ChargeDecay { decay })
+        }
+        _ => panic!("unknown charging model \"{}\"", model),
+    }
+}
+
+// compares the exact solver to trying every hold time, for all races up to --max-time (default 100)
+#[allow(dead_code)]
+pub fn verify() {
+    let args = std::env::args().collect::<Vec<String>>();
+    let max_time = match args.iter().position(|arg| arg == "--max-time") {
+        None => 100,
+        Some(index) => args.get(index + 1).unwrap().parse::<i64>().unwrap(),
+    };
+
+    let mut mismatches = 0;
+    let mut races = 0;
+
+    for time in 0..=max_time {
+        // one distance past the best possible one, so races that cannot be won are checked as well
+        for distance in 0..=(time * time / 4 + 1) {
+            let race = Race { time: BigInt::from(time), distance: BigInt::from(distance) };
+
+            let expected = (0..=time).filter(|hold_time| race.wins(&BigInt::from(*hold_time))).count() as i64;
+            let actual = race.calculate_number_of_ways_to_win();
+            let searched = race.count_winning_hold_times(&Linear);
+
+            if actual != BigInt::from(expected) || searched != BigInt::from(expected) {
+                println!("time {}, distance {}: expected {}, got {} (search: {})", time, distance, expected, actual, searched);
+                mismatches += 1;
+            }
+
+            races += 1;
+        }
+    }
+
+    println!("{} races checked, {} mismatches", races, mismatches);
+}
+
 struct Race {
-    time: i64,
-    distance: i64,
+    time: BigInt,
+    distance: BigInt,
 }
 
 impl Race {
-    fn calculate_number_of_ways_to_win(&self) -> i64 {
+    fn calculate_number_of_ways_to_win(&self) -> BigInt {
         // graphs: f(x) = (time - x) * x, g(x) = distance
-        // => equation to solve: (time - x) * x > distance <=> -x^2 + time * x - distance > 0
-        // using quadratic formula: x_1,x_2 = (-time +/- sqrt(time^2 - 4 * distance)) / -2
+        // => equation to solve: (time - x) * x > distance <=> x^2 - time * x + distance < 0
+        // using quadratic formula: x_1,x_2 = (time -/+ sqrt(time^2 - 4 * distance)) / 2
+        // f is symmetric around time / 2, so with x_min being the smallest winning hold time
+        // all hold times in x_min..=(time - x_min) win
+
+        let time = &self.time;
+        let two = BigInt::from(2i64);
+
+        let discriminant = time * time - &self.distance * &BigInt::from(4i64);
+        if discriminant.is_negative() {
+            return BigInt::zero();
+        }
+
+        // the integer square root can be off from the real root by less than one,
+        // so correct the estimate until it is exactly the first winning hold time
+        let mut min_x = (time - &discriminant.sqrt()).div_floor(&two);
+
+        while !self.wins(&min_x) && &min_x * &two <= *time {
+            min_x = min_x + BigInt::one();
+        }
+
+        while !min_x.is_zero() && self.wins(&(&min_x - &BigInt::one())) {
+            min_x = min_x - BigInt::one();
+        }
+
+        if &min_x * &two > *time {
+            return BigInt::zero();
+        }
+
+        time - &(min_x * two) + BigInt::one()
+    }
+
+    fn wins(&self, hold_time: &BigInt) -> bool {
+        (&self.time - hold_time) * hold_time > self.distance
+    }
+
+    // the distance only grows up to the optimum and only shrinks after it, so the winning hold times
+    // form a single range around the optimum, and both of its ends are found with a binary search
+    fn count_winning_hold_times(&self, model: &dyn ChargingModel) -> BigInt {
+        let wins = |hold_time: &BigInt| model.distance(&self.time, hold_time) > self.distance;
+
+        let optimum = model.optimum(&self.time);
+        if !wins(&optimum) {
+            return BigInt::zero();
+        }
+
+        let first = first_failing(&BigInt::zero(), &optimum, |hold_time| !wins(hold_time));
+        let end = first_failing(&optimum, &(&self.time + &BigInt::one()), wins);
+
+        end - first
+    }
+}
+
+// the first value in start..end for which the predicate is false (or end if there is none), the
+// predicate has to be true for all values before that one and false for all values after it
+fn first_failing<F: Fn(&BigInt) -> bool>(start: &BigInt, end: &BigInt, predicate: F) -> BigInt {
+    let mut low = start.clone();
+    let mut high = end.clone();
+
+    while low < high {
+        let middle = (&low + &high).div_floor(&BigInt::from(2i64));
+
+        match predicate(&middle) {
+            true => low = middle + BigInt::one(),
+            false => high = middle,
+        }
+    }
+
+    low
+}
+
+trait ChargingModel {
+    // distance travelled in a race of `time` milliseconds when holding the button for `hold_time` of them
+    fn distance(&self, time: &BigInt, hold_time: &BigInt) -> BigInt;
+
+    // a hold time with the largest distance, found with a ternary search, which needs the distance
+    // to strictly grow up to the largest one and strictly shrink after it (apart from a flat top)
+    fn optimum(&self, time: &BigInt) -> BigInt {
+        let three = BigInt::from(3i64);
+
+        let mut low = BigInt::zero();
+        let mut high = time.clone();
+
+        while &high - &low >= three {
+            let third = (&high - &low) / &three;
+            let middle_low = &low + &third;
+            let middle_high = &high - &third;
+
+            match self.distance(time, &middle_low) < self.distance(time, &middle_high) {
+                true => low = middle_low + BigInt::one(),
+                false => high = middle_high,
+            }
+        }
+
+        let mut optimum = low.clone();
+        while low < high {
+            low = low + BigInt::one();
 
-        let time = self.time as f64;
-        let distance = self.distance as f64;
+            if self.distance(time, &low) > self.distance(time, &optimum) {
+                optimum = low.clone();
+            }
+        }
 
-        let d = (time * time - 4.0 * distance).sqrt();
+        optimum
+    }
+}
+
+// the puzzle rules: every millisecond of holding adds one millimeter per millisecond of speed
+struct Linear;
+
+impl ChargingModel for Linear {
+    fn distance(&self, time: &BigInt, hold_time: &BigInt) -> BigInt {
+        (time - hold_time) * hold_time
+    }
+
+    fn optimum(&self, time: &BigInt) -> BigInt {
+        time.div_floor(&BigInt::from(2i64))
+    }
+}
+
+// the speed grows with the square of the hold time
+struct QuadraticAcceleration;
+
+impl ChargingModel for QuadraticAcceleration {
+    fn distance(&self, time: &BigInt, hold_time: &BigInt) -> BigInt {
+        (time - hold_time) * hold_time * hold_time
+    }
+}
+
+// holding the button longer than it takes to reach the maximum speed only wastes time
+struct CappedSpeed {
+    max_speed: BigInt,
+}
+
+impl ChargingModel for CappedSpeed {
+    fn distance(&self, time: &BigInt, hold_time: &BigInt) -> BigInt {
+        (time - hold_time) * hold_time.min(&self.max_speed)
+    }
+}
+
+// the boat loses `decay` of its speed every millisecond it moves, until it stops
+struct ChargeDecay {
+    decay: BigInt,
+}
 
-        let x_1: f64 = (-time + d) / -2.0;
-        let x_2: f64 = (-time - d) / -2.0;
+impl ChargingModel for ChargeDecay {
+    fn distance(&self, time: &BigInt, hold_time: &BigInt) -> BigInt {
+        let one = BigInt::one();
 
-        let min_x = (x_1 + 1.0).floor();
-        let max_x = (x_2 - 1.0).ceil();
+        // number of milliseconds the boat moves at all
+        let moving_time = (hold_time + &(&self.decay - &one)).div_floor(&self.decay).min(time - hold_time);
 
-        max_x as i64 - min_x as i64 + 1
+        // hold_time + (hold_time - decay) + (hold_time - 2 * decay) + ... for every moving millisecond
+        &moving_time * hold_time - &self.decay * &(&moving_time * &(&moving_time - &one)).div_floor(&BigInt::from(2i64))
     }
 }
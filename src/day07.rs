@@ -1,9 +1,11 @@
 use std::cmp::Ordering;
-use std::collections::{HashMap};
+use std::collections::HashMap;
 use std::fs::File;
 use std::io::{BufRead, BufReader};
 use std::iter::zip;
-use std::str::FromStr;
+use std::time::Instant;
+use crate::bigint::BigInt;
+use crate::rational::Rational;
 
 #[allow(dead_code)]
 pub fn main() {
@@ -18,136 +20,457 @@ pub fn main() {
     println!("PART 2: {}", part2(&lines));
 }
 
-fn part1(lines: &Vec<String>) -> i32 {
-    let mut hands = lines
+// plays the input with the rule set from --rules <file>, see RuleSet::from_file for the format
+#[allow(dead_code)]
+pub fn custom() {
+    let input_file = File::open("inputs/07.txt").unwrap();
+
+    let lines = BufReader::new(input_file)
+        .lines()
+        .map(|line| line.unwrap())
+        .collect::<Vec<String>>();
+
+    let args = std::env::args().collect::<Vec<String>>();
+    let rules = match args.iter().position(|arg| arg == "--rules") {
+        None => RuleSet::jokers(),
+        Some(index) => RuleSet::from_file(args.get(index + 1).unwrap()).unwrap(),
+    };
+
+    let hands = lines
         .iter()
-        .map(|line| Hand::from_str(line).unwrap())
+        .map(|line| Hand::parse(line, &rules).unwrap())
         .collect::<Vec<Hand>>();
 
-    hands.sort_by(|a, b| a.compare(b));
+    for (hand_type_index, hand_type) in rules.hand_types.iter().enumerate() {
+        let count = hands.iter().filter(|hand| hand.get_hand_type(&rules) == hand_type_index).count();
+        println!("{}: {}", hand_type.name, count);
+    }
 
-    hands.iter()
-        .enumerate()
-        .map(|(i, hand)| (i as i32 + 1) * hand.bid as i32)
-        .sum()
+    println!("TOTAL WINNINGS: {}", total_winnings(lines.as_slice(), &rules));
+}
+
+// sorts a million random hands (--hands <count>) by comparing them pairwise and by their strength keys
+#[allow(dead_code)]
+pub fn benchmark() {
+    let args = std::env::args().collect::<Vec<String>>();
+    let hand_count = match args.iter().position(|arg| arg == "--hands") {
+        None => 1_000_000,
+        Some(index) => args.get(index + 1).unwrap().parse::<usize>().unwrap(),
+    };
+
+    let rules = RuleSet::jokers();
+
+    // xorshift, so the generated hands are the same for every run
+    let mut state = 0x2545f4914f6cdd1du64;
+    let mut random = |bound: usize| {
+        state ^= state << 13;
+        state ^= state >> 7;
+        state ^= state << 17;
+        (state % bound as u64) as usize
+    };
+
+    let lines = (0..hand_count)
+        .map(|_| {
+            let cards = (0..rules.hand_size)
+                .map(|_| rules.card_order[random(rules.card_order.len())])
+                .collect::<String>();
+
+            format!("{} {}", cards, random(1000))
+        })
+        .collect::<Vec<String>>();
+
+    let start = Instant::now();
+    let hands = lines.iter().map(|line| Hand::parse(line, &rules).unwrap()).collect::<Vec<Hand>>();
+    println!("parsing (including strength keys): {:?}", start.elapsed());
+
+    let mut compared_hands = hands.iter().collect::<Vec<&Hand>>();
+    let start = Instant::now();
+    compared_hands.sort_by(|a, b| a.compare(b, &rules));
+    println!("sorting by comparing hands: {:?}", start.elapsed());
+
+    let mut keyed_hands = hands.iter().collect::<Vec<&Hand>>();
+    let start = Instant::now();
+    keyed_hands.sort_by_key(|hand| hand.strength);
+    println!("sorting by strength keys: {:?}", start.elapsed());
+
+    let same_order = zip(&compared_hands, &keyed_hands).all(|(a, b)| a.strength == b.strength);
+    println!("same order: {}", same_order);
+}
+
+// exact odds of every hand type when completing a partial hand (--hand AAJ) from the remaining cards,
+// --deck A=4,K=4,... describes the deck (by default four of every card) before the hand was dealt
+#[allow(dead_code)]
+pub fn odds() {
+    let args = std::env::args().collect::<Vec<String>>();
+    let arg = |name: &str| args.iter().position(|arg| arg == name).map(|index| args.get(index + 1).unwrap().as_str());
+
+    let partial_hand = arg("--hand").unwrap_or("");
+    let deck = arg("--deck").map(parse_deck).transpose().unwrap();
+
+    for (title, rules) in [("WITHOUT JOKERS", RuleSet::standard()), ("WITH JOKERS", RuleSet::jokers())] {
+        println!("{}:", title);
+
+        match hand_type_odds(partial_hand, deck.as_deref(), &rules) {
+            Err(error) => println!("  {}", error),
+            Ok(probabilities) => {
+                for (hand_type, probability) in zip(&rules.hand_types, probabilities) {
+                    // in millionths, i.e. ten-thousandths of a percent, rounded down
+                    let millionths = (&probability * &Rational::from(1_000_000)).floor().to_i64().unwrap();
+                    println!("  {:<16} {:>3}.{:04}%  {}", hand_type.name, millionths / 10000, millionths % 10000, probability);
+                }
+            }
+        }
+    }
+}
+
+// A=4,K=4,Q=4
+fn parse_deck(s: &str) -> Result<Vec<(char, u32)>, String> {
+    s.split(',')
+        .map(|entry| {
+            let (card, count) = entry.split_once('=').ok_or(format!("expected \"<card>=<count>\", got \"{}\"", entry))?;
+
+            let mut chars = card.trim().chars();
+            let card = match (chars.next(), chars.next()) {
+                (Some(card), None) => card,
+                _ => return Err(format!("expected a single card, got \"{}\"", card)),
+            };
+
+            Ok((card, count.trim().parse::<u32>().map_err(|_| format!("invalid count \"{}\"", count))?))
+        })
+        .collect()
+}
+
+// probability of every hand type of the rule set, drawing the missing cards without replacement
+fn hand_type_odds(partial_hand: &str, deck: Option<&[(char, u32)]>, rules: &RuleSet) -> Result<Vec<Rational>, String> {
+    let card_index = |card: char| rules.card_order.iter()
+        .position(|c| *c == card)
+        .ok_or(format!("unknown card '{}'", card));
+
+    let mut remaining = vec![0u32; rules.card_order.len()];
+    match deck {
+        None => remaining.iter_mut().for_each(|count| *count = 4),
+        Some(deck) => {
+            for (card, count) in deck {
+                remaining[card_index(*card)?] += count;
+            }
+        }
+    }
+
+    let mut cards = vec![];
+    for card in partial_hand.chars() {
+        let card = card_index(card)?;
+
+        if remaining[card] == 0 {
+            return Err(format!("no '{}' left in the deck", rules.card_order[card]));
+        }
+
+        remaining[card] -= 1;
+        cards.push(card as u8);
+    }
+
+    if cards.len() > rules.hand_size {
+        return Err(format!("a hand has only {} cards", rules.hand_size));
+    }
+
+    let mut ways_per_hand_type = vec![BigInt::zero(); rules.hand_types.len()];
+
+    let draws = (rules.hand_size - cards.len()) as u32;
+    enumerate_draws(&remaining, 0, draws, &mut cards, BigInt::one(), &mut |cards, ways| {
+        let hand_type = Hand::get_hand_type_from_cards(cards, rules);
+        ways_per_hand_type[hand_type] = &ways_per_hand_type[hand_type] + ways;
+    });
+
+    let total_ways = ways_per_hand_type.iter().cloned().sum::<BigInt>();
+    if total_ways.is_zero() {
+        return Err(format!("not enough cards left to draw {} more", draws));
+    }
+
+    Ok(ways_per_hand_type
+        .into_iter()
+        .map(|ways| Rational::new(ways, total_ways.clone()))
+        .collect())
+}
+
+// every multiset of `draws` cards out of the remaining ones (taking cards in order from `card` on),
+// together with the number of ways to draw it
+fn enumerate_draws<F: FnMut(&[u8], &BigInt)>(remaining: &[u32], card: usize, draws: u32, cards: &mut Vec<u8>, ways: BigInt, visit: &mut F) {
+    if draws == 0 {
+        visit(cards, &ways);
+        return;
+    }
+
+    if card == remaining.len() {
+        return;
+    }
+
+    for count in 0..=draws.min(remaining[card]) {
+        cards.extend((0..count).map(|_| card as u8));
+        enumerate_draws(remaining, card + 1, draws - count, cards, &ways * &binomial(remaining[card], count), visit);
+        cards.truncate(cards.len() - count as usize);
+    }
+}
+
+fn binomial(n: u32, k: u32) -> BigInt {
+    // every intermediate result is a binomial coefficient itself, so the divisions are exact
+    (0..k).fold(BigInt::one(), |result, i| result * BigInt::from((n - i) as u64) / BigInt::from((i + 1) as u64))
+}
+
+fn part1(lines: &Vec<String>) -> u32 {
+    total_winnings(lines, &RuleSet::standard())
+}
+
+fn part2(lines: &Vec<String>) -> u32 {
+    total_winnings(lines, &RuleSet::jokers())
 }
 
-fn part2(lines: &Vec<String>) -> i32 {
+fn total_winnings(lines: &[String], rules: &RuleSet) -> u32 {
     let mut hands = lines
         .iter()
-        .map(|line| line.replace("J", "*"))
-        .map(|line| Hand::from_str(&line).unwrap())
+        .map(|line| Hand::parse(line, rules).unwrap())
         .collect::<Vec<Hand>>();
 
-    hands.sort_by(|a, b| a.compare(b));
+    hands.sort_by_key(|hand| hand.strength);
 
     hands.iter()
         .enumerate()
-        .map(|(i, hand)| (i as i32 + 1) * hand.bid as i32)
+        .map(|(i, hand)| (i as u32 + 1) * hand.bid)
         .sum()
 }
 
-struct Hand {
-    cards: Vec<u8>,
-    bid: u32,
+struct RuleSet {
+    // weakest card first
+    card_order: Vec<char>,
+    wild_cards: Vec<char>,
+    hand_size: usize,
+    // weakest hand type first, a hand has the strongest type it can satisfy
+    hand_types: Vec<HandType>,
 }
 
-impl Hand {
-    fn get_hand_type(&self) -> u8 {
-        let mut initial_card_count_per_type = self.cards.iter().
-            fold(HashMap::new(), |mut map, card| {
-                map.entry(card).and_modify(|count| *count += 1).or_insert(1);
-                map
-            });
-
-        if !initial_card_count_per_type.contains_key(&0) || initial_card_count_per_type.len() == 1 {
-            return Hand::get_hand_type_from_cards(&initial_card_count_per_type);
-        }
+struct HandType {
+    name: String,
+    pattern: HandPattern,
+}
 
-        let joker_count = *initial_card_count_per_type.get(&0).unwrap();
-        initial_card_count_per_type.remove(&0);
+enum HandPattern {
+    // groups of equal cards of (at least) these sizes, biggest first, e.g. [3, 2] for a full house
+    GROUPS(Vec<usize>),
+    // cards of consecutive ranks (not counting wild cards as ranks)
+    STRAIGHT,
+}
 
-        initial_card_count_per_type.keys()
-            .map(|card_type| {
-                let mut card_counts_per_type = initial_card_count_per_type.clone();
-                card_counts_per_type.entry(card_type).and_modify(|count| *count += joker_count);
-                Hand::get_hand_type_from_cards(&card_counts_per_type)
-            })
-            .max()
-            .unwrap()
+impl RuleSet {
+    fn standard() -> RuleSet {
+        RuleSet {
+            card_order: "23456789TJQKA".chars().collect(),
+            wild_cards: vec![],
+            hand_size: 5,
+            hand_types: RuleSet::classic_hand_types(),
+        }
     }
 
-    fn get_hand_type_from_cards(card_count_per_type: &HashMap<&u8, i32>) -> u8 {
-        // five of a kind
-        if card_count_per_type.values().any(|count| *count == 5) {
-            return 6;
+    fn jokers() -> RuleSet {
+        RuleSet {
+            card_order: "J23456789TQKA".chars().collect(),
+            wild_cards: vec!['J'],
+            hand_size: 5,
+            hand_types: RuleSet::classic_hand_types(),
         }
+    }
 
-        // four of a kind
-        if card_count_per_type.values().any(|count| *count == 4) {
-            return 5;
-        }
+    fn classic_hand_types() -> Vec<HandType> {
+        [
+            ("high card", vec![]),
+            ("one pair", vec![2]),
+            ("two pair", vec![2, 2]),
+            ("three of a kind", vec![3]),
+            ("full house", vec![3, 2]),
+            ("four of a kind", vec![4]),
+            ("five of a kind", vec![5]),
+        ]
+            .into_iter()
+            .map(|(name, group_sizes)| HandType { name: name.to_string(), pattern: HandPattern::GROUPS(group_sizes) })
+            .collect()
+    }
 
-        // full house
-        if card_count_per_type.values().all(|count| *count == 3 || *count == 2) {
-            return 4;
+    // one setting per line, hand types from weakest to strongest:
+    //   cards 23456789TJQKA
+    //   wild J
+    //   size 5
+    //   type high card =
+    //   type one pair = 2
+    //   type straight = straight
+    //   type full house = 3 2
+    fn from_file(path: &str) -> Result<RuleSet, String> {
+        let content = std::fs::read_to_string(path).map_err(|error| format!("{}: {}", path, error))?;
+
+        let mut rules = RuleSet { card_order: vec![], wild_cards: vec![], hand_size: 5, hand_types: vec![] };
+
+        for (index, line) in content.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+
+            let error = |message: &str| format!("{}:{}: {}", path, index + 1, message);
+
+            let (setting, value) = line.split_once(' ').ok_or(error("expected \"<setting> <value>\""))?;
+            let value = value.trim();
+
+            match setting {
+                "cards" => rules.card_order = value.chars().collect(),
+                "wild" => rules.wild_cards = value.chars().collect(),
+                "size" => rules.hand_size = value.parse().map_err(|_| error("invalid hand size"))?,
+                "type" => {
+                    let (name, pattern) = value.split_once('=').ok_or(error("expected \"type <name> = <pattern>\""))?;
+
+                    let pattern = match pattern.trim() {
+                        "straight" => HandPattern::STRAIGHT,
+                        group_sizes => {
+                            let mut group_sizes = group_sizes
+                                .split_whitespace()
+                                .map(|size| size.parse::<usize>())
+                                .collect::<Result<Vec<usize>, _>>()
+                                .map_err(|_| error("invalid group sizes"))?;
+
+                            // the matcher pairs the biggest sizes with the biggest groups, so "2 3" is a full house too
+                            group_sizes.sort_by(|a, b| b.cmp(a));
+                            HandPattern::GROUPS(group_sizes)
+                        }
+                    };
+
+                    rules.hand_types.push(HandType { name: name.trim().to_string(), pattern });
+                }
+                _ => return Err(error(&format!("unknown setting \"{}\"", setting))),
+            }
         }
 
-        // three of a kind
-        if card_count_per_type.values().any(|count| *count == 3) {
-            return 3;
+        if rules.wild_cards.iter().any(|card| !rules.card_order.contains(card)) {
+            return Err(format!("{}: wild cards have to be part of the card order", path));
         }
 
-        // two pair
-        if card_count_per_type.len() == 3 && card_count_per_type.values().all(|count| *count == 2 || *count == 1) {
-            return 2;
+        // the weakest type has to match every hand, so every hand has a type
+        match rules.hand_types.first() {
+            Some(HandType { pattern: HandPattern::GROUPS(group_sizes), .. }) if group_sizes.is_empty() => Ok(rules),
+            _ => Err(format!("{}: the weakest hand type has to be \"type <name> =\"", path)),
         }
+    }
 
-        // one pair
-        if card_count_per_type.values().any(|count| *count == 2) {
-            return 1;
-        }
+    fn is_wild(&self, card: u8) -> bool {
+        self.wild_cards.contains(&self.card_order[card as usize])
+    }
+}
 
-        // high card
-        return 0;
+impl HandPattern {
+    fn is_satisfied_by(&self, cards: &[u8], rules: &RuleSet) -> bool {
+        let wild_count = cards.iter().filter(|card| rules.is_wild(**card)).count();
+
+        match self {
+            HandPattern::GROUPS(group_sizes) => {
+                let mut counts = cards.iter()
+                    .filter(|card| !rules.is_wild(**card))
+                    .fold(HashMap::new(), |mut counts, card| {
+                        *counts.entry(card).or_insert(0) += 1;
+                        counts
+                    })
+                    .into_values()
+                    .collect::<Vec<usize>>();
+
+                // the biggest groups are the cheapest to complete, so they are matched with the biggest sizes
+                counts.sort_by(|a, b| b.cmp(a));
+
+                let missing_cards = group_sizes.iter()
+                    .enumerate()
+                    .map(|(i, size)| size.saturating_sub(*counts.get(i).unwrap_or(&0)))
+                    .sum::<usize>();
+
+                missing_cards <= wild_count
+            }
+            HandPattern::STRAIGHT => {
+                // position among the cards that are not wild, wild cards fill the gaps
+                let rank_of = |card: u8| (0..card).filter(|other| !rules.is_wild(*other)).count();
+
+                let mut ranks = cards.iter()
+                    .filter(|card| !rules.is_wild(**card))
+                    .map(|card| rank_of(*card))
+                    .collect::<Vec<usize>>();
+                ranks.sort();
+
+                let distinct = ranks.windows(2).all(|pair| pair[0] != pair[1]);
+                let span = ranks.last().zip(ranks.first()).map_or(0, |(last, first)| last - first + 1);
+                let rank_count = rank_of(rules.card_order.len() as u8);
+
+                distinct && span <= cards.len() && cards.len() <= rank_count
+            }
+        }
     }
+}
 
+struct Hand {
+    // positions in the card order of the rule set
+    cards: Vec<u8>,
+    bid: u32,
+    // hand type followed by the cards, packed so that comparing keys is comparing hands
+    strength: u128,
+}
 
-    fn compare(&self, other: &Hand) -> Ordering {
-        let type_delta = (self.get_hand_type() as i8) - (other.get_hand_type() as i8);
+impl Hand {
+    fn parse(s: &str, rules: &RuleSet) -> Result<Hand, String> {
+        let (cards, bid) = s.split_once(' ').ok_or(format!("expected \"<cards> <bid>\", got \"{}\"", s))?;
 
-        if type_delta > 0 {
-            return Ordering::Greater;
-        }
-        if type_delta < 0 {
-            return Ordering::Less;
+        let bid = bid.trim().parse::<u32>().map_err(|_| format!("invalid bid \"{}\"", bid))?;
+
+        let cards = cards.chars()
+            .map(|card| rules.card_order.iter()
+                .position(|c| *c == card)
+                .map(|position| position as u8)
+                .ok_or(format!("unknown card '{}'", card))
+            )
+            .collect::<Result<Vec<u8>, String>>()?;
+
+        if cards.len() != rules.hand_size {
+            return Err(format!("expected {} cards, got {}", rules.hand_size, cards.len()));
         }
 
-        zip(&self.cards, &other.cards)
-            .find_map(|(a, b)| {
-                if a.cmp(b) != Ordering::Equal {
-                    return Some(a.cmp(b));
-                }
+        let strength = Hand::strength_key(&cards, rules)
+            .ok_or(format!("{} cards do not fit into a strength key", cards.len()))?;
 
-                None
-            }).unwrap()
+        Ok(Hand { cards, bid, strength })
     }
-}
 
-const CARD_RANKS: &'static [char] = &['*', '2', '3', '4', '5', '6', '7', '8', '9', 'T', 'J', 'Q', 'K', 'A'];
+    fn strength_key(cards: &[u8], rules: &RuleSet) -> Option<u128> {
+        let card_bits = usize::BITS - (rules.card_order.len() - 1).leading_zeros();
+        let hand_type_bits = usize::BITS - (rules.hand_types.len() - 1).leading_zeros();
 
-impl FromStr for Hand {
-    type Err = ();
+        if hand_type_bits + card_bits * cards.len() as u32 > u128::BITS {
+            return None;
+        }
 
-    fn from_str(s: &str) -> Result<Self, Self::Err> {
-        let split = &s.split(" ").collect::<Vec<&str>>()[..];
+        let key = cards
+            .iter()
+            .fold(Hand::get_hand_type_from_cards(cards, rules) as u128, |key, card| (key << card_bits) | *card as u128);
 
-        let bid = split.get(1).unwrap().parse::<u32>().unwrap();
+        Some(key)
+    }
+
+    // index into the hand types of the rule set
+    fn get_hand_type(&self, rules: &RuleSet) -> usize {
+        Hand::get_hand_type_from_cards(&self.cards, rules)
+    }
 
-        let cards = split.get(0).unwrap().chars()
-            .map(|card| CARD_RANKS.iter().position(|c| *c == card).unwrap() as u8)
-            .collect();
+    fn get_hand_type_from_cards(cards: &[u8], rules: &RuleSet) -> usize {
+        rules.hand_types
+            .iter()
+            .rposition(|hand_type| hand_type.pattern.is_satisfied_by(cards, rules))
+            .unwrap()
+    }
 
-        Ok(Hand { cards, bid })
+    fn compare(&self, other: &Hand, rules: &RuleSet) -> Ordering {
+        self.get_hand_type(rules)
+            .cmp(&other.get_hand_type(rules))
+            .then_with(|| zip(&self.cards, &other.cards)
+                .map(|(a, b)| a.cmp(b))
+                .find(|ordering| *ordering != Ordering::Equal)
+                .unwrap_or(Ordering::Equal)
+            )
     }
 }
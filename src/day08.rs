@@ -1,8 +1,9 @@
-use std::collections::HashMap;
 use std::fs::File;
 use std::io::{BufReader, Read};
 use std::str::FromStr;
 use regex::Regex;
+use crate::bigint::BigInt;
+use crate::interner::Interner;
 
 #[allow(dead_code)]
 pub fn main() {
@@ -11,87 +12,280 @@ pub fn main() {
     let mut input = String::new();
     BufReader::new(input_file).read_to_string(&mut input).unwrap();
 
-    let map = Map::from_str(&input).unwrap();
+    let map = Map::parse(&input, &alphabet_from_args()).unwrap();
 
     println!("PART 1: {}", part1(&map));
-    println!("PART 2: {}", part2(&map));
+    println!("PART 2: {}", part2(&map).map_or("never".to_string(), |steps| steps.to_string()));
+}
+
+// --alphabet L=0,R=1,M=2 maps instruction symbols to the child they lead to
+fn alphabet_from_args() -> InstructionAlphabet {
+    let args = std::env::args().collect::<Vec<String>>();
+
+    match args.iter().position(|arg| arg == "--alphabet") {
+        None => InstructionAlphabet::default(),
+        Some(index) => args.get(index + 1).unwrap().parse().unwrap(),
+    }
 }
 
 fn part1(map: &Map) -> u64 {
-    map.calculate_path_length("AAA", &vec!["ZZZ"])
+    let end_nodes = map.nodes.names()
+        .map(|(_, node)| node == "ZZZ")
+        .collect::<Vec<bool>>();
+
+    map.calculate_path_length(map.nodes.get("AAA").unwrap(), &end_nodes)
 }
 
-fn part2(map: &Map) -> u64 {
-    let end_nodes = map.graph.keys().filter(|node| node.ends_with("Z"))
-        .map(|node| &node[..]).collect::<Vec<&str>>();
+// the first step at which all ghosts are on an end node at the same time, if there is one
+fn part2(map: &Map) -> Option<BigInt> {
+    let end_nodes = map.nodes.names()
+        .map(|(_, node)| node.ends_with('Z'))
+        .collect::<Vec<bool>>();
+
+    let arrivals = map.nodes.names()
+        .filter(|(_, node)| node.ends_with('A'))
+        .map(|(start_node, _)| map.find_arrivals(start_node, &end_nodes))
+        .collect::<Vec<Arrivals>>();
+
+    first_common_arrival(&arrivals)
+}
+
+// all steps at which a ghost is on an end node: the ones before its walk starts repeating, and every
+// step congruent to one of the cycle steps (modulo the cycle length) from the start of the cycle on
+struct Arrivals {
+    before_cycle: Vec<u64>,
+    cycle_start: u64,
+    cycle_length: u64,
+    in_cycle: Vec<u64>,
+}
+
+impl Arrivals {
+    fn contains(&self, step: u64) -> bool {
+        match step < self.cycle_start {
+            true => self.before_cycle.contains(&step),
+            false => self.in_cycle.iter().any(|arrival| (step + self.cycle_length - arrival).is_multiple_of(self.cycle_length)),
+        }
+    }
+}
+
+fn first_common_arrival(arrivals: &[Arrivals]) -> Option<BigInt> {
+    let latest_cycle = arrivals.iter().max_by_key(|arrivals| arrivals.cycle_start)?;
+
+    // before all ghosts are in their cycles, the ghost entering its cycle last has to be on an end node
+    if let Some(step) = latest_cycle.before_cycle.iter().find(|step| arrivals.iter().all(|arrivals| arrivals.contains(**step))) {
+        return Some(BigInt::from(*step));
+    }
+
+    // afterwards every ghost only adds a choice of congruences, all combinations are solved with the crt
+    let mut congruences = vec![(BigInt::zero(), BigInt::one())];
+
+    for arrivals in arrivals {
+        let modulus = BigInt::from(arrivals.cycle_length);
 
-    map.graph.keys()
-        .filter(|node| node.ends_with("A"))
-        .fold(1, |lcm_path_length, start_node| {
-            lcm(map.calculate_path_length(start_node, &end_nodes), lcm_path_length)
+        congruences = congruences
+            .iter()
+            .flat_map(|(residue, combined_modulus)| arrivals.in_cycle
+                .iter()
+                .filter_map(|arrival| solve_congruences(residue, combined_modulus, &BigInt::from(*arrival), &modulus))
+                .collect::<Vec<(BigInt, BigInt)>>()
+            )
+            .collect();
+    }
+
+    // the smallest step at or after the latest cycle start for every solution
+    let latest_cycle_start = BigInt::from(latest_cycle.cycle_start);
+
+    congruences
+        .iter()
+        .map(|(residue, modulus)| {
+            let behind = modulo(&(residue - &latest_cycle_start), modulus);
+            &latest_cycle_start + &behind
         })
+        .min()
+}
+
+// x = a (mod n) and x = b (mod m) combined into x = c (mod lcm(n, m)), moduli do not have to be coprime
+fn solve_congruences(a: &BigInt, n: &BigInt, b: &BigInt, m: &BigInt) -> Option<(BigInt, BigInt)> {
+    let g = n.gcd(m);
+
+    let difference = b - a;
+    if !modulo(&difference, &g).is_zero() {
+        return None;
+    }
+
+    // a + n * k = b (mod m) <=> k = (b - a) / g * (n / g)^-1 (mod m / g)
+    let m_reduced = m / &g;
+    let (_, n_inverse, _) = extended_gcd(&modulo(&(n / &g), &m_reduced), &m_reduced);
+    let k = modulo(&(difference / &g * n_inverse), &m_reduced);
+
+    let lcm = n * &m_reduced;
+    Some((modulo(&(a + &(n * &k)), &lcm), lcm))
+}
+
+// (gcd(a, b), x, y) with a * x + b * y = gcd(a, b)
+fn extended_gcd(a: &BigInt, b: &BigInt) -> (BigInt, BigInt, BigInt) {
+    if b.is_zero() {
+        return (a.clone(), BigInt::one(), BigInt::zero());
+    }
+
+    let (quotient, remainder) = a.div_rem(b);
+    let (g, x, y) = extended_gcd(b, &remainder);
+
+    (g, y.clone(), x - quotient * y)
+}
+
+fn modulo(a: &BigInt, n: &BigInt) -> BigInt {
+    a - &(n * &a.div_floor(n))
 }
 
 struct Map {
-    graph: HashMap<String, (String, String)>,
-    instructions: Vec<char>,
+    nodes: Interner,
+    children: Vec<Vec<usize>>,
+    // the index of the child every instruction leads to
+    instructions: Vec<usize>,
 }
 
 impl Map {
-    fn calculate_path_length(&self, start_node: &str, end_nodes: &Vec<&str>) -> u64 {
-        let mut current_node = start_node;
-        let mut current_instruction_index = 0;
-        let mut path_length = 0;
+    fn parse(s: &str, alphabet: &InstructionAlphabet) -> Result<Map, String> {
+        let graph_line_regex = Regex::new(r"^([0-9A-Za-z]+) = \(([0-9A-Za-z]+(?:, *[0-9A-Za-z]+)*)\)$").unwrap();
 
-        while !end_nodes.contains(&current_node) {
-            let (left_node, right_node) = self.graph.get(current_node).unwrap();
+        let instructions = s.lines()
+            .next()
+            .ok_or("missing instructions")?
+            .chars()
+            .map(|symbol| alphabet.children
+                .iter()
+                .find(|(other, _)| *other == symbol)
+                .map(|(_, child)| *child)
+                .ok_or(format!("line 1: unknown instruction '{}'", symbol))
+            )
+            .collect::<Result<Vec<usize>, String>>()?;
 
-            current_node = match self.instructions.get(current_instruction_index).unwrap() {
-                'L' => left_node,
-                'R' => right_node,
-                _ => current_node
-            };
+        if instructions.is_empty() {
+            return Err("line 1: missing instructions".to_string());
+        }
 
-            path_length += 1;
-            current_instruction_index = (current_instruction_index + 1) % self.instructions.len();
+        let mut nodes = Interner::new();
+        let mut children: Vec<Option<Vec<usize>>> = Vec::new();
+
+        for (index, line) in s.lines().enumerate().skip(2) {
+            let groups = graph_line_regex.captures(line)
+                .ok_or(format!("line {}: expected \"<node> = (<child>, <child>, ...)\"", index + 1))?;
+
+            let node_id = nodes.intern(&groups[1]);
+            let node_children = groups[2]
+                .split(',')
+                .map(|child| nodes.intern(child.trim()))
+                .collect::<Vec<usize>>();
+
+            children.resize(nodes.len(), None);
+
+            if children[node_id].is_some() {
+                return Err(format!("line {}: node {} is defined twice", index + 1, &groups[1]));
+            }
+
+            children[node_id] = Some(node_children);
         }
 
-        path_length
+        children.resize(nodes.len(), None);
+
+        let longest_instruction = instructions.iter().max().unwrap();
+
+        let children = nodes.names()
+            .map(|(id, name)| match &children[id] {
+                None => Err(format!("node {} is never defined", name)),
+                Some(node_children) if node_children.len() <= *longest_instruction => {
+                    Err(format!("node {} has no child {} to follow", name, longest_instruction))
+                }
+                Some(node_children) => Ok(node_children.clone()),
+            })
+            .collect::<Result<Vec<Vec<usize>>, String>>()?;
+
+        Ok(Map { nodes, children, instructions })
     }
-}
 
-impl FromStr for Map {
-    type Err = ();
+    // walks until a (node, instruction index) state repeats, from then on the walk is periodic
+    fn find_arrivals(&self, start_node: usize, end_nodes: &[bool]) -> Arrivals {
+        let mut first_visits: Vec<Option<u64>> = vec![None; self.nodes.len() * self.instructions.len()];
+        let mut end_node_steps = vec![];
 
-    fn from_str(s: &str) -> Result<Self, Self::Err> {
-        let graph_line_regex = Regex::new(r"^([A-Z]{3}) = \(([A-Z]{3}), ([A-Z]{3})\)$").unwrap();
+        let mut current_node = start_node;
+        let mut step = 0u64;
+
+        let cycle_start = loop {
+            let instruction_index = (step % self.instructions.len() as u64) as usize;
+            let state = current_node * self.instructions.len() + instruction_index;
+
+            if let Some(first_visit) = first_visits[state] {
+                break first_visit;
+            }
+
+            first_visits[state] = Some(step);
+
+            if end_nodes[current_node] {
+                end_node_steps.push(step);
+            }
 
-        let instructions = s.lines().next().unwrap().chars().collect::<Vec<char>>();
+            current_node = self.next_node(current_node, instruction_index);
+            step += 1;
+        };
 
-        let graph = s.lines().skip(2)
-            .fold(HashMap::new(), |mut graph, line| {
-                let groups = graph_line_regex.captures(line).unwrap();
+        let (in_cycle, before_cycle) = end_node_steps.into_iter().partition(|end_node_step| *end_node_step >= cycle_start);
 
-                let node_id = String::from_str(groups.get(1).unwrap().as_str()).unwrap();
-                let left_child = String::from_str(groups.get(2).unwrap().as_str()).unwrap();
-                let right_child = String::from_str(groups.get(3).unwrap().as_str()).unwrap();
+        Arrivals { before_cycle, cycle_start, cycle_length: step - cycle_start, in_cycle }
+    }
+
+    fn next_node(&self, node: usize, instruction_index: usize) -> usize {
+        self.children[node][self.instructions[instruction_index]]
+    }
+
+    fn calculate_path_length(&self, start_node: usize, end_nodes: &[bool]) -> u64 {
+        let mut current_node = start_node;
+        let mut current_instruction_index = 0;
+        let mut path_length = 0;
 
-                graph.insert(node_id, (left_child, right_child));
-                graph
-            });
+        while !end_nodes[current_node] {
+            current_node = self.next_node(current_node, current_instruction_index);
 
-        Ok(Map { graph, instructions })
+            path_length += 1;
+            current_instruction_index = (current_instruction_index + 1) % self.instructions.len();
+        }
+
+        path_length
     }
 }
 
-fn lcm(a: u64, b: u64) -> u64 {
-    a * b / gcd(a, b)
+// which child every instruction symbol leads to, by default L for the first and R for the second one
+struct InstructionAlphabet {
+    children: Vec<(char, usize)>,
 }
 
-fn gcd(a: u64, b: u64) -> u64 {
-    if b == 0 {
-        return a;
+impl Default for InstructionAlphabet {
+    fn default() -> Self {
+        InstructionAlphabet { children: vec![('L', 0), ('R', 1)] }
     }
+}
 
-    return gcd(b, a % b);
+// L=0,R=1,M=2
+impl FromStr for InstructionAlphabet {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let children = s
+            .split(',')
+            .map(|entry| {
+                let (symbol, child) = entry.split_once('=').ok_or(format!("expected \"<symbol>=<child index>\", got \"{}\"", entry))?;
+
+                let mut chars = symbol.trim().chars();
+                let symbol = match (chars.next(), chars.next()) {
+                    (Some(symbol), None) => symbol,
+                    _ => return Err(format!("expected a single symbol, got \"{}\"", symbol)),
+                };
+
+                Ok((symbol, child.trim().parse::<usize>().map_err(|_| format!("invalid child index \"{}\"", child))?))
+            })
+            .collect::<Result<Vec<(char, usize)>, String>>()?;
+
+        Ok(InstructionAlphabet { children })
+    }
 }
@@ -1,6 +1,9 @@
+use std::fmt;
 use std::fs::File;
 use std::io::{BufRead, BufReader};
 use std::str::FromStr;
+use crate::bigint::BigInt;
+use crate::rational::Rational;
 
 #[allow(dead_code)]
 pub fn main() {
@@ -15,53 +18,192 @@ pub fn main() {
     println!("PART 2: {}", part2(&lines));
 }
 
-fn part1(lines: &Vec<String>) -> i64 {
+// prints the polynomial behind every sequence and its values --steps (default 1) before and after it
+#[allow(dead_code)]
+pub fn analyze() {
+    let input_file = File::open("inputs/09.txt").unwrap();
+
+    let lines = BufReader::new(input_file)
+        .lines()
+        .map(|line| line.unwrap())
+        .collect::<Vec<String>>();
+
+    let args = std::env::args().collect::<Vec<String>>();
+    let steps = match args.iter().position(|arg| arg == "--steps") {
+        None => 1,
+        Some(index) => args.get(index + 1).unwrap().parse::<i64>().unwrap(),
+    };
+
+    for line in &lines {
+        let sequence = Sequence::from_str(line).unwrap();
+
+        match sequence.fit() {
+            None => println!("{}: not polynomial within its {} values", line, sequence.values.len()),
+            Some(polynomial) => println!(
+                "{}: degree {}, p(x) = {}, {} steps back: {}, {} steps ahead: {}",
+                line,
+                polynomial.degree().map_or("-".to_string(), |degree| degree.to_string()),
+                polynomial,
+                steps,
+                sequence.extrapolate(-steps),
+                steps,
+                sequence.extrapolate(steps),
+            ),
+        }
+    }
+}
+
+fn part1(lines: &Vec<String>) -> BigInt {
     lines
         .iter()
         .map(|line| Sequence::from_str(line).unwrap())
-        .map(|sequence| sequence.extrapolate_next_value())
+        .map(|sequence| sequence.extrapolate(1))
         .sum()
 }
 
-fn part2(lines: &Vec<String>) -> i64 {
+fn part2(lines: &Vec<String>) -> BigInt {
     lines
         .iter()
         .map(|line| Sequence::from_str(line).unwrap())
-        .map(|sequence| sequence.extrapolate_previous_value())
+        .map(|sequence| sequence.extrapolate(-1))
         .sum()
 }
 
 struct Sequence {
-    values: Vec<i64>,
+    values: Vec<BigInt>,
 }
 
 impl Sequence {
-    fn extrapolate_next_value(&self) -> i64 {
-        if self.is_all_zero() {
-            return 0;
+    // the polynomial of the lowest degree through all values in newton form, at most n - 1 for n values
+    fn interpolate(&self) -> Polynomial {
+        let mut differences = vec![];
+        let mut row = self.values.clone();
+
+        while !row.iter().all(|value| value.is_zero()) {
+            differences.push(row[0].clone());
+
+            row = row.windows(2)
+                .map(|pair| &pair[1] - &pair[0])
+                .collect();
         }
 
-        return self.values.last().unwrap() + self.derivative().extrapolate_next_value();
+        Polynomial { differences }
     }
 
-    fn extrapolate_previous_value(&self) -> i64 {
-        if self.is_all_zero() {
-            return 0;
+    // the interpolating polynomial, but only if the differences become all zero before running out of
+    // values, a single non-zero difference says nothing about the next one
+    fn fit(&self) -> Option<Polynomial> {
+        let polynomial = self.interpolate();
+
+        match polynomial.differences.len() < self.values.len() {
+            true => Some(polynomial),
+            false => None,
         }
+    }
+
+    // positive steps go past the last value, negative ones before the first value
+    fn extrapolate(&self, steps: i64) -> BigInt {
+        let x = match steps >= 0 {
+            true => self.values.len() as i64 - 1 + steps,
+            false => steps,
+        };
 
-        return self.values.first().unwrap() - self.derivative().extrapolate_previous_value();
+        self.interpolate().value_at(&BigInt::from(x))
     }
+}
 
-    fn derivative(&self) -> Sequence {
-        let values = (0..self.values.len() - 1)
-            .map(|i| self.values.get(i + 1).unwrap() - self.values.get(i).unwrap())
-            .collect::<Vec<i64>>();
+// sum of differences[k] * binomial(x, k), with x = 0 at the first value of the sequence
+struct Polynomial {
+    differences: Vec<BigInt>,
+}
 
-        Sequence { values }
+impl Polynomial {
+    // the zero polynomial has no degree
+    fn degree(&self) -> Option<usize> {
+        self.differences.len().checked_sub(1)
     }
 
-    fn is_all_zero(&self) -> bool {
-        self.values.iter().all(|value| *value == 0)
+    fn value_at(&self, x: &BigInt) -> BigInt {
+        let mut binomial = BigInt::one();
+        let mut value = BigInt::zero();
+
+        for (k, difference) in self.differences.iter().enumerate() {
+            value = value + difference * &binomial;
+
+            // binomial(x, k + 1) = binomial(x, k) * (x - k) / (k + 1), the division is always exact
+            binomial = binomial * (x - &BigInt::from(k as u64)) / BigInt::from(k as u64 + 1);
+        }
+
+        value
+    }
+
+    // coefficients of 1, x, x^2, ... of the same polynomial
+    fn coefficients(&self) -> Vec<Rational> {
+        let mut coefficients = vec![Rational::zero(); self.differences.len()];
+
+        // binomial(x, k) = x * (x - 1) * ... * (x - k + 1) / k!, the product expanded into its coefficients
+        let mut falling_factorial = vec![BigInt::one()];
+        let mut factorial = BigInt::one();
+
+        for (k, difference) in self.differences.iter().enumerate() {
+            for (power, coefficient) in falling_factorial.iter().enumerate() {
+                coefficients[power] = &coefficients[power] + &Rational::new(difference * coefficient, factorial.clone());
+            }
+
+            let k = BigInt::from(k as u64);
+            let mut next_falling_factorial = vec![BigInt::zero(); falling_factorial.len() + 1];
+
+            for (power, coefficient) in falling_factorial.iter().enumerate() {
+                next_falling_factorial[power + 1] = &next_falling_factorial[power + 1] + coefficient;
+                next_falling_factorial[power] = &next_falling_factorial[power] - &(coefficient * &k);
+            }
+
+            falling_factorial = next_falling_factorial;
+            factorial = factorial * (k + BigInt::one());
+        }
+
+        coefficients
+    }
+}
+
+impl fmt::Display for Polynomial {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let terms = self.coefficients()
+            .into_iter()
+            .enumerate()
+            .rev()
+            .filter(|(_, coefficient)| *coefficient != Rational::zero())
+            .collect::<Vec<(usize, Rational)>>();
+
+        if terms.is_empty() {
+            return write!(f, "0");
+        }
+
+        for (i, (power, coefficient)) in terms.iter().enumerate() {
+            let magnitude = match coefficient.is_negative() {
+                true => -coefficient,
+                false => coefficient.clone(),
+            };
+
+            match (i, coefficient.is_negative()) {
+                (0, true) => write!(f, "-")?,
+                (0, false) => {}
+                (_, true) => write!(f, " - ")?,
+                (_, false) => write!(f, " + ")?,
+            }
+
+            if *power == 0 || magnitude != Rational::from(1) {
+                write!(f, "{}", magnitude)?;
+            }
+
+            match power {
+                0 => {}
+                1 => write!(f, "x")?,
+                _ => write!(f, "x^{}", power)?,
+            }
+        }
+
+        Ok(())
     }
 }
 
@@ -70,8 +212,8 @@ impl FromStr for Sequence {
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
         let values = s.split(" ")
-            .map(|number| number.parse::<i64>().unwrap())
-            .collect::<Vec<i64>>();
+            .map(|number| BigInt::from_str(number).unwrap())
+            .collect::<Vec<BigInt>>();
 
         Ok(Sequence { values })
     }
@@ -1,7 +1,7 @@
-use std::cmp::Ordering;
-use std::collections::{BinaryHeap, HashMap, VecDeque};
 use std::fs::File;
 use std::io::{BufRead, BufReader};
+use crate::animation::Color;
+use crate::geometry::{interior_points, signed_doubled_area};
 
 #[allow(dead_code)]
 pub fn main() {
@@ -13,124 +13,203 @@ pub fn main() {
         .collect::<Vec<String>>();
 
     println!("PART 1: {}", part1(&lines));
+    println!("PART 2: {}", part2(&lines));
 }
 
-fn part1(lines: &Vec<String>) -> usize {
-    let graph = parse_graph(lines);
+// prints the maze with box-drawing characters, only the main loop in color, and some facts about the loop
+#[allow(dead_code)]
+pub fn render() {
+    let input_file = File::open("inputs/10.txt").unwrap();
 
-    #[derive(Copy, Clone, Eq, PartialEq)]
-    struct State {
-        distance: usize,
-        position: (usize, usize),
-    }
+    let lines = BufReader::new(input_file)
+        .lines()
+        .map(|line| line.unwrap())
+        .collect::<Vec<String>>();
 
-    impl PartialOrd<Self> for State {
-        fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
-            Some(self.cmp(other))
-        }
+    let maze = Maze::parse(&lines).unwrap();
+    let pipe_loop = maze.find_loop().unwrap();
+
+    let mut on_loop = vec![vec![false; maze.tiles[0].len()]; maze.tiles.len()];
+    for (x, y) in &pipe_loop {
+        on_loop[*y][*x] = true;
     }
 
-    impl Ord for State {
-        fn cmp(&self, other: &Self) -> Ordering {
-            other.distance.cmp(&self.distance).then_with(|| self.position.cmp(&other.position))
+    for (y, row) in maze.tiles.iter().enumerate() {
+        let mut line = String::new();
+        let mut current_color = None;
+
+        for (x, tile) in row.iter().enumerate() {
+            let (symbol, color) = match () {
+                _ if (x, y) == maze.start => (box_drawing(*tile), Color::Red),
+                _ if on_loop[y][x] => (box_drawing(*tile), Color::Yellow),
+                // pipes that are not part of the loop are just junk
+                _ => ('·', Color::Gray),
+            };
+
+            if current_color != Some(color) {
+                line += &format!("\x1b[{}m", color.ansi_code());
+                current_color = Some(color);
+            }
+
+            line.push(symbol);
         }
+
+        println!("{}\x1b[0m", line);
     }
 
-    let mut heap = BinaryHeap::new();
-    heap.push(State { distance: 0, position: graph.start_node });
+    let corners = loop_corners(&pipe_loop);
 
-    let mut distances = HashMap::new();
-    distances.insert(graph.start_node, 0);
+    // y points down, so a positive area means clockwise on the screen
+    let orientation = match signed_doubled_area(&corners) > 0 {
+        true => "clockwise",
+        false => "counterclockwise",
+    };
 
-    while let Some(state) = heap.pop() {
-        if distances.contains_key(&state.position) && *distances.get(&state.position).unwrap() < state.distance {
-            continue;
-        }
+    println!("start tile: {}", box_drawing(maze.tiles[maze.start.1][maze.start.0]));
+    println!("loop length: {} (farthest tile {} steps away)", pipe_loop.len(), pipe_loop.len() / 2);
+    println!("orientation: {}", orientation);
+    println!("enclosed tiles: {}", interior_points(&corners));
+}
 
-        for connection in &graph.nodes.get(&state.position).unwrap().connections {
-            let connection_state = State { position: *connection, distance: state.distance + 1 };
+fn part1(lines: &Vec<String>) -> usize {
+    // the loop has an even length, since it returns to its start on a grid
+    Maze::parse(lines).unwrap().find_loop().unwrap().len() / 2
+}
 
-            if !distances.contains_key(connection) || *distances.get(connection).unwrap() > connection_state.distance {
-                heap.push(connection_state);
-                distances.entry(*connection)
-                    .and_modify(|distance| *distance = connection_state.distance)
-                    .or_insert(connection_state.distance);
-            }
+fn part2(lines: &Vec<String>) -> i64 {
+    let pipe_loop = Maze::parse(lines).unwrap().find_loop().unwrap();
+    interior_points(&loop_corners(&pipe_loop))
+}
+
+// every tile of the loop is a corner of the polygon it encloses
+fn loop_corners(pipe_loop: &[(usize, usize)]) -> Vec<(i64, i64)> {
+    pipe_loop.iter()
+        .map(|(x, y)| (*x as i64, *y as i64))
+        .collect()
+}
+
+#[derive(Copy, Clone, Eq, PartialEq)]
+enum Direction {
+    NORTH,
+    EAST,
+    SOUTH,
+    WEST,
+}
+
+impl Direction {
+    const ALL: [Direction; 4] = [Direction::NORTH, Direction::EAST, Direction::SOUTH, Direction::WEST];
+
+    fn opposite(&self) -> Direction {
+        match self {
+            Direction::NORTH => Direction::SOUTH,
+            Direction::EAST => Direction::WEST,
+            Direction::SOUTH => Direction::NORTH,
+            Direction::WEST => Direction::EAST,
         }
     }
+}
 
-    *distances.values().max().unwrap()
+// the two directions a pipe connects, nothing for ground (and for the start before it is inferred)
+fn connections(tile: char) -> &'static [Direction] {
+    match tile {
+        '|' => &[Direction::NORTH, Direction::SOUTH],
+        '-' => &[Direction::EAST, Direction::WEST],
+        'L' => &[Direction::NORTH, Direction::EAST],
+        'J' => &[Direction::NORTH, Direction::WEST],
+        '7' => &[Direction::SOUTH, Direction::WEST],
+        'F' => &[Direction::EAST, Direction::SOUTH],
+        _ => &[],
+    }
 }
 
-fn parse_graph(lines: &Vec<String>) -> Graph {
-    let map = lines.iter()
-        .map(|line| line.chars().collect::<Vec<char>>())
-        .collect::<Vec<Vec<char>>>();
-
-    let (start_x, start_y) = map.iter().enumerate()
-        .find_map(|(y, row)|
-            row.iter().enumerate().find_map(|(x, c)| {
-                if *c == 'S' {
-                    return Some((x, y));
-                }
-                None
-            })
-        ).unwrap();
-
-    let start_node = Node {
-        connections: vec![
-            (start_x - 1, start_y),
-            (start_x + 1, start_y),
-            (start_x, start_y - 1),
-            (start_x, start_y + 1),
-        ].iter()
-            .filter(|(adjacent_x, adjacent_y)|
-                get_node_connections(&map, *adjacent_x, *adjacent_y)
-                    .iter()
-                    .any(|(x, y)| *x == start_x && *y == start_y)
-            )
-            .map(|(x, y)| (*x, *y))
-            .collect(),
-    };
+fn box_drawing(tile: char) -> char {
+    match tile {
+        '|' => '│',
+        '-' => '─',
+        'L' => '└',
+        'J' => '┘',
+        '7' => '┐',
+        'F' => '┌',
+        _ => ' ',
+    }
+}
 
-    let mut queue = VecDeque::from(start_node.connections.clone());
+struct Maze {
+    // the start tile is replaced by the pipe it has to be
+    tiles: Vec<Vec<char>>,
+    start: (usize, usize),
+}
 
-    let mut nodes = HashMap::new();
-    nodes.insert((start_x, start_y), start_node);
+impl Maze {
+    fn parse(lines: &[String]) -> Result<Maze, String> {
+        let tiles = lines.iter()
+            .map(|line| line.chars().collect::<Vec<char>>())
+            .collect::<Vec<Vec<char>>>();
 
-    while let Some((x, y)) = queue.pop_front() {
-        if nodes.contains_key(&(x, y)) {
-            continue;
+        if tiles.iter().any(|row| row.len() != tiles[0].len()) {
+            return Err("all rows have to be equally long".to_string());
         }
 
-        let connections = get_node_connections(&map, x, y);
-        for connection in &connections {
-            queue.push_back(*connection)
-        }
+        let start = tiles.iter()
+            .enumerate()
+            .find_map(|(y, row)| row.iter().position(|tile| *tile == 'S').map(|x| (x, y)))
+            .ok_or("there is no start tile")?;
 
-        nodes.insert((x, y), Node { connections });
-    }
+        let mut maze = Maze { tiles, start };
 
-    Graph { nodes, start_node: (start_x, start_y) }
-}
+        // the start connects to every neighbour that connects back to it
+        let start_connections = Direction::ALL
+            .into_iter()
+            .filter(|direction| maze.neighbour(start, *direction)
+                .is_some_and(|(x, y)| connections(maze.tiles[y][x]).contains(&direction.opposite()))
+            )
+            .collect::<Vec<Direction>>();
+
+        maze.tiles[start.1][start.0] = "|-LJ7F"
+            .chars()
+            .find(|tile| connections(*tile) == start_connections.as_slice())
+            .ok_or(format!("the start connects to {} pipes instead of 2", start_connections.len()))?;
 
-fn get_node_connections(map: &Vec<Vec<char>>, x: usize, y: usize) -> Vec<(usize, usize)> {
-    match map[y][x] {
-        '|' => vec![(x, y - 1), (x, y + 1)],
-        '-' => vec![(x - 1, y), (x + 1, y)],
-        'L' => vec![(x, y - 1), (x + 1, y)],
-        'J' => vec![(x, y - 1), (x - 1, y)],
-        '7' => vec![(x, y + 1), (x - 1, y)],
-        'F' => vec![(x, y + 1), (x + 1, y)],
-        _ => vec![],
+        Ok(maze)
     }
-}
 
-struct Graph {
-    nodes: HashMap<(usize, usize), Node>,
-    start_node: (usize, usize),
-}
+    fn neighbour(&self, (x, y): (usize, usize), direction: Direction) -> Option<(usize, usize)> {
+        let (x, y) = match direction {
+            Direction::NORTH => (x, y.checked_sub(1)?),
+            Direction::EAST => (x + 1, y),
+            Direction::SOUTH => (x, y + 1),
+            Direction::WEST => (x.checked_sub(1)?, y),
+        };
+
+        match y < self.tiles.len() && x < self.tiles[y].len() {
+            true => Some((x, y)),
+            false => None,
+        }
+    }
+
+    // the tiles of the loop through the start, in walking order
+    fn find_loop(&self) -> Result<Vec<(usize, usize)>, String> {
+        let mut pipe_loop = vec![self.start];
+        let mut position = self.start;
+        let mut direction = connections(self.tiles[self.start.1][self.start.0])[0];
+
+        loop {
+            position = self.neighbour(position, direction)
+                .ok_or(format!("the pipe at {:?} leads off the map", position))?;
+
+            if position == self.start {
+                return Ok(pipe_loop);
+            }
+
+            let tile_connections = connections(self.tiles[position.1][position.0]);
+            if !tile_connections.contains(&direction.opposite()) {
+                return Err(format!("the loop is broken at {:?}", position));
+            }
 
-struct Node {
-    connections: Vec<(usize, usize)>,
+            // leave the pipe through the other end than the one we came in through
+            direction = *tile_connections.iter().find(|other| **other != direction.opposite()).unwrap();
+
+            pipe_loop.push(position);
+        }
+    }
 }
@@ -1,5 +1,6 @@
 use std::fs::File;
 use std::io::{BufRead, BufReader};
+use std::str::FromStr;
 
 #[allow(dead_code)]
 pub fn main() {
@@ -15,75 +16,227 @@ pub fn main() {
 }
 
 fn part1(lines: &Vec<String>) -> i64 {
-    parse_image(lines, 2).calculate_distance_sum()
+    parse_image(lines, 2, 2).manhattan_distance_sum()
 }
 
 fn part2(lines: &Vec<String>) -> i64 {
-    parse_image(lines, 1000000).calculate_distance_sum()
+    parse_image(lines, 1000000, 1000000).manhattan_distance_sum()
 }
 
-fn parse_image(lines: &Vec<String>, expansion_factor: usize) -> Image {
+// prints the distance sum and the nearest neighbour of every galaxy, e.g. --metric euclidean
+// --row-expansion 2 --column-expansion 10
+#[allow(dead_code)]
+pub fn nearest() {
+    let input_file = File::open("inputs/11.txt").unwrap();
+
+    let lines = BufReader::new(input_file)
+        .lines()
+        .map(|line| line.unwrap())
+        .collect::<Vec<String>>();
+
+    let args = std::env::args().collect::<Vec<String>>();
+    let arg = |name: &str| args.iter().position(|arg| arg == name).map(|index| args.get(index + 1).unwrap().as_str());
+
+    // an empty row (column) is replaced by `factor` of them, so there has to be at least one left
+    let expansion = |name: &str| match arg(name).map_or(Ok(2), |factor| factor.parse::<usize>()) {
+        Ok(factor) if factor >= 1 => Ok(factor),
+        _ => Err(format!("{} has to be a whole number of at least 1", name)),
+    };
+
+    let metric = match arg("--metric").map_or(Ok(Metric::MANHATTAN), |metric| metric.parse()) {
+        Ok(metric) => metric,
+        Err(_) => {
+            println!("--metric has to be manhattan, chebyshev or euclidean");
+            return;
+        }
+    };
+
+    let (row_expansion, column_expansion) = match (expansion("--row-expansion"), expansion("--column-expansion")) {
+        (Ok(row_expansion), Ok(column_expansion)) => (row_expansion, column_expansion),
+        (Err(error), _) | (_, Err(error)) => {
+            println!("{}", error);
+            return;
+        }
+    };
+
+    let image = parse_image(&lines, row_expansion, column_expansion);
+
+    match metric {
+        Metric::MANHATTAN => println!("distance sum: {}", image.manhattan_distance_sum()),
+        Metric::CHEBYSHEV => println!("distance sum: {}", image.chebyshev_distance_sum()),
+        Metric::EUCLIDEAN => println!("distance sum: {:.3}", image.euclidean_distance_sum()),
+    }
+
+    for (galaxy, (neighbour, distance)) in image.galaxies.iter().zip(image.nearest_neighbours(metric)) {
+        println!("{:?} -> {:?} ({:.3})", galaxy, image.galaxies[neighbour], distance);
+    }
+}
+
+fn parse_image(lines: &Vec<String>, row_expansion: usize, column_expansion: usize) -> Image {
     let image = lines.iter()
         .map(|line| line.chars().collect::<Vec<char>>())
         .collect::<Vec<Vec<char>>>();
 
-    let rows_without_galaxies = image.iter()
-        .enumerate()
-        .filter(|(_, row)| row.iter().all(|c| *c != '#'))
-        .map(|(y, _)| y)
-        .collect::<Vec<usize>>();
-
-    let columns_without_galaxies = (0..image.get(0).unwrap().len())
-        .filter(|x| {
-            image.iter().all(|row| *(row.get(*x).unwrap()) != '#')
-        })
-        .collect::<Vec<usize>>();
+    // the number of empty rows (columns) before each row (column), so every galaxy is moved in constant time
+    let empty_rows_before = count_empty_before(image.iter().map(|row| row.iter().all(|c| *c != '#')));
+    let empty_columns_before = count_empty_before(
+        (0..image[0].len()).map(|x| image.iter().all(|row| row[x] != '#'))
+    );
 
     let galaxies = image
         .iter()
         .enumerate()
-        .map(|(y, row)| row
+        .flat_map(|(y, row)| row
             .iter()
             .enumerate()
-            .filter_map(move |(x, c)| {
-                if *c == '#' {
-                    return Some((x, y));
-                }
-                return None;
-            })
+            .filter(|(_, c)| **c == '#')
+            .map(move |(x, _)| (x, y))
         )
-        .flatten()
-        .filter_map(|(x, y)| {
-            let rows_without_galaxy_before = rows_without_galaxies.iter()
-                .filter(|row_y| **row_y < y).count();
-
-            let columns_without_galaxy_before = columns_without_galaxies.iter()
-                .filter(|column_x| **column_x < x).count();
-
-            return Some((
-                x + columns_without_galaxy_before * (expansion_factor - 1),
-                y + rows_without_galaxy_before * (expansion_factor - 1),
-            ));
-        })
+        .map(|(x, y)| (
+            x + empty_columns_before[x] * (column_expansion - 1),
+            y + empty_rows_before[y] * (row_expansion - 1),
+        ))
         .collect::<Vec<(usize, usize)>>();
 
     Image { galaxies }
 }
 
+fn count_empty_before<I: Iterator<Item=bool>>(is_empty: I) -> Vec<usize> {
+    is_empty
+        .scan(0, |count, empty| {
+            let before = *count;
+            *count += empty as usize;
+            Some(before)
+        })
+        .collect()
+}
+
+// the sum of |a - b| over all pairs, once sorted every value is larger than all values before it,
+// so it adds to the sum once for each of them, minus their total
+fn axis_distance_sum(mut values: Vec<i64>) -> i64 {
+    values.sort();
+
+    let mut sum = 0;
+    let mut prefix_sum = 0;
+
+    for (i, value) in values.iter().enumerate() {
+        sum += value * i as i64 - prefix_sum;
+        prefix_sum += value;
+    }
+
+    sum
+}
+
+#[derive(Copy, Clone)]
+enum Metric {
+    MANHATTAN,
+    CHEBYSHEV,
+    EUCLIDEAN,
+}
+
+impl Metric {
+    fn distance(&self, (x_a, y_a): (usize, usize), (x_b, y_b): (usize, usize)) -> f64 {
+        let dx = (x_a as f64 - x_b as f64).abs();
+        let dy = (y_a as f64 - y_b as f64).abs();
+
+        match self {
+            Metric::MANHATTAN => dx + dy,
+            Metric::CHEBYSHEV => dx.max(dy),
+            Metric::EUCLIDEAN => dx.hypot(dy),
+        }
+    }
+}
+
+impl FromStr for Metric {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "manhattan" => Ok(Metric::MANHATTAN),
+            "chebyshev" => Ok(Metric::CHEBYSHEV),
+            "euclidean" => Ok(Metric::EUCLIDEAN),
+            _ => Err(()),
+        }
+    }
+}
+
 struct Image {
     galaxies: Vec<(usize, usize)>,
 }
 
 impl Image {
-    fn calculate_distance_sum(&self) -> i64 {
-        let mut sum = 0;
+    // the manhattan distance splits into the distances along both axes, which are summed separately
+    fn manhattan_distance_sum(&self) -> i64 {
+        axis_distance_sum(self.galaxies.iter().map(|(x, _)| *x as i64).collect())
+            + axis_distance_sum(self.galaxies.iter().map(|(_, y)| *y as i64).collect())
+    }
 
-        for (i, (x_a, y_a)) in self.galaxies.iter().enumerate() {
-            for (x_b, y_b) in &self.galaxies[i..] {
-                sum += (*x_b as i64 - *x_a as i64).abs() + (*y_b as i64 - *y_a as i64).abs();
+    // rotating by 45 degrees turns max(|dx|, |dy|) into (|du| + |dv|) / 2 with u = x + y and v = x - y,
+    // every pair has an even |du| + |dv|, so halving the total is exact
+    fn chebyshev_distance_sum(&self) -> i64 {
+        let u = self.galaxies.iter().map(|(x, y)| *x as i64 + *y as i64).collect();
+        let v = self.galaxies.iter().map(|(x, y)| *x as i64 - *y as i64).collect();
+
+        (axis_distance_sum(u) + axis_distance_sum(v)) / 2
+    }
+
+    // the euclidean distance does not split into axes, so all pairs are summed
+    fn euclidean_distance_sum(&self) -> f64 {
+        let mut sum = 0.0;
+
+        for (i, a) in self.galaxies.iter().enumerate() {
+            for b in &self.galaxies[i + 1..] {
+                sum += Metric::EUCLIDEAN.distance(*a, *b);
             }
         }
 
         sum
     }
+
+    // the index of and distance to the closest other galaxy, for every galaxy, no metric is smaller
+    // than |dx|, so the search walks outwards through the galaxies sorted by x and stops once |dx|
+    // alone is larger than the best distance found
+    fn nearest_neighbours(&self, metric: Metric) -> Vec<(usize, f64)> {
+        let mut by_x = (0..self.galaxies.len()).collect::<Vec<usize>>();
+        by_x.sort_by_key(|index| self.galaxies[*index]);
+
+        let mut nearest = vec![(0, f64::INFINITY); self.galaxies.len()];
+
+        for (position, index) in by_x.iter().enumerate() {
+            let galaxy = self.galaxies[*index];
+            let mut best = (0, f64::INFINITY);
+
+            // false once the galaxy and all further ones on that side are too far away
+            let mut visit = |other: usize| {
+                let other_galaxy = self.galaxies[other];
+
+                if (other_galaxy.0 as f64 - galaxy.0 as f64).abs() > best.1 {
+                    return false;
+                }
+
+                let distance = metric.distance(galaxy, other_galaxy);
+                if distance < best.1 {
+                    best = (other, distance);
+                }
+
+                true
+            };
+
+            for other in by_x[..position].iter().rev() {
+                if !visit(*other) {
+                    break;
+                }
+            }
+
+            for other in &by_x[position + 1..] {
+                if !visit(*other) {
+                    break;
+                }
+            }
+
+            nearest[*index] = best;
+        }
+
+        nearest
+    }
 }
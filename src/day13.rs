@@ -2,6 +2,7 @@ use std::cmp::min;
 use std::fs::File;
 use std::io::{BufRead, BufReader};
 use std::iter::zip;
+use crate::bitset::BitSet;
 
 #[allow(dead_code)]
 pub fn main() {
@@ -19,25 +20,19 @@ fn part1(lines: &Vec<String>) -> u64 {
     lines
         .split(|line| line.is_empty())
         .map(|pattern| {
-            let vertical_line = (1..pattern[0].len()).find_map(|line_index| {
-                if is_pattern_symmetrical_to_vertical_line(pattern, line_index) {
-                    return Some(line_index);
-                }
-                return None;
-            });
+            let rows = pattern.iter()
+                .map(|line| BitSet::from_bools(line.chars().map(|c| c == '#')))
+                .collect::<Vec<BitSet>>();
 
-            if let Some(line_index) = vertical_line {
+            let columns = (0..pattern[0].len())
+                .map(|x| BitSet::from_bools(rows.iter().map(|row| row.contains(x))))
+                .collect::<Vec<BitSet>>();
+
+            if let Some(line_index) = find_mirror_line(&columns) {
                 return line_index as u64;
             }
 
-            let horizontal_line = (1..pattern.len()).find_map(|line_index| {
-                if is_pattern_symmetrical_to_horizontal_line(pattern, line_index) {
-                    return Some(line_index);
-                }
-                return None;
-            });
-
-            if let Some(line_index) = horizontal_line {
+            if let Some(line_index) = find_mirror_line(&rows) {
                 return (line_index as u64) * 100;
             }
 
@@ -47,22 +42,16 @@ fn part1(lines: &Vec<String>) -> u64 {
         .sum()
 }
 
-fn is_pattern_symmetrical_to_vertical_line(pattern: &[String], line_index: usize) -> bool {
-    let min_side_width = min(line_index, pattern[0].len() - line_index);
-
-    let left = pattern.iter()
-        .map(|line| (&line[line_index - min_side_width..line_index]).chars().rev().collect::<String>());
-    let right = pattern.iter()
-        .map(|line| String::from(&line[line_index..line_index + min_side_width]));
-
-    zip(left, right).all(|(a, b)| a == b)
+// a mirror line between index - 1 and index, so that all rows (or columns) reflect onto each other
+fn find_mirror_line(lines: &[BitSet]) -> Option<usize> {
+    (1..lines.len()).find(|line_index| count_differences_to_mirror_line(lines, *line_index) == 0)
 }
 
-fn is_pattern_symmetrical_to_horizontal_line(pattern: &[String], line_index: usize) -> bool {
-    let min_side_width = min(line_index, pattern.len() - line_index);
+fn count_differences_to_mirror_line(lines: &[BitSet], line_index: usize) -> usize {
+    let min_side_width = min(line_index, lines.len() - line_index);
 
-    let top = (&pattern[line_index - min_side_width..line_index]).iter().rev();
-    let bottom = (&pattern[line_index..line_index + min_side_width]).iter();
+    let before = lines[line_index - min_side_width..line_index].iter().rev();
+    let after = lines[line_index..line_index + min_side_width].iter();
 
-    zip(top, bottom).all(|(a, b)| a == b)
+    zip(before, after).map(|(a, b)| a.xor_distance(b)).sum()
 }
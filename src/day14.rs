@@ -1,6 +1,9 @@
 use std::collections::HashMap;
 use std::fs::File;
 use std::io::{BufRead, BufReader};
+use crate::animation::{animate, AnimationOptions, Cell, Color, Frame};
+use crate::bitset::BitSet;
+use crate::simulation::{Simulation, Simulator};
 
 #[allow(dead_code)]
 pub fn main() {
@@ -15,54 +18,59 @@ pub fn main() {
     println!("PART 2: {}", part2(&lines));
 }
 
+#[allow(dead_code)]
+pub fn animation() {
+    let input_file = File::open("inputs/14.txt").unwrap();
+
+    let lines = BufReader::new(input_file)
+        .lines()
+        .map(|line| line.unwrap())
+        .collect::<Vec<String>>();
+
+    animate(platform_from_lines(&lines).unwrap(), &AnimationOptions::from_args()).unwrap();
+}
+
 fn part1(lines: &Vec<String>) -> u64 {
     platform_from_lines(lines)
+        .unwrap()
         .tilt_vertical(true)
         .calculate_total_load()
 }
 
 fn part2(lines: &Vec<String>) -> u64 {
-    let mut platform = platform_from_lines(lines);
+    const CYCLES: usize = 1000000000;
+
+    let mut simulator = Simulator::new(platform_from_lines(lines).unwrap());
 
     let mut cache = HashMap::new();
+    cache.insert(simulator.state().fingerprint(), 0);
 
-    let mut cycles_remaining = 1000000000;
-    while cycles_remaining > 0 {
-        platform = platform.cycle();
-        cycles_remaining -= 1;
+    let loop_start = loop {
+        simulator.step();
 
-        let fingerprint = platform.fingerprint();
-        if !cache.contains_key(&fingerprint) {
-            cache.insert(fingerprint, cycles_remaining);
-            continue;
+        let fingerprint = simulator.state().fingerprint();
+        if let Some(step) = cache.get(&fingerprint) {
+            break *step;
         }
 
-        let loop_size = cache.get(&fingerprint).unwrap() - cycles_remaining;
-        cycles_remaining %= loop_size;
-    }
+        cache.insert(fingerprint, simulator.current_step());
+    };
+
+    let loop_size = simulator.current_step() - loop_start;
 
-    platform.calculate_total_load()
+    simulator.snapshot(loop_start + (CYCLES - loop_start) % loop_size)
+        .unwrap()
+        .calculate_total_load()
 }
 
+#[derive(Clone)]
 struct Platform {
-    rows: Vec<Vec<char>>,
+    // one bitset per row, containing the columns with a rock in them
+    rounded_rocks: Vec<Row>,
+    cube_rocks: Vec<Row>,
 }
 
 impl Platform {
-    fn columns(&self) -> Vec<Vec<char>> {
-        (0..self.rows.first().unwrap().len())
-            .map(|x| self.rows
-                .iter()
-                .map(|line| *(line.iter().nth(x).unwrap()))
-                .collect::<Vec<char>>()
-            )
-            .collect()
-    }
-
-    fn rows(&self) -> Vec<Vec<char>> {
-        self.rows.clone()
-    }
-
     fn cycle(&self) -> Platform {
         self
             .tilt_vertical(true)
@@ -72,74 +80,114 @@ impl Platform {
     }
 
     fn tilt_vertical(&self, north: bool) -> Platform {
-        platform_from_columns(self
-            .columns()
-            .iter()
-            .map(|column| tilt(column, north))
-            .collect()
-        )
+        let mut rounded_rocks = self.rounded_rocks.clone();
+
+        // moving against the tilt direction lets a rock roll all the way within a single pass,
+        // another pass is only needed to confirm that nothing moves anymore
+        let mut changed = true;
+        while changed {
+            changed = false;
+
+            for i in 0..rounded_rocks.len() - 1 {
+                let (from, to) = match north {
+                    true => (rounded_rocks.len() - 1 - i, rounded_rocks.len() - 2 - i),
+                    false => (i, i + 1),
+                };
+
+                let free = rounded_rocks[to].union(&self.cube_rocks[to]).complement();
+                let moving = rounded_rocks[from].intersection(&free);
+
+                if !moving.is_empty() {
+                    rounded_rocks[from] = rounded_rocks[from].difference(&moving);
+                    rounded_rocks[to] = rounded_rocks[to].union(&moving);
+                    changed = true;
+                }
+            }
+        }
+
+        Platform { rounded_rocks, cube_rocks: self.cube_rocks.clone() }
     }
 
     fn tilt_horizontal(&self, west: bool) -> Platform {
-        platform_from_rows(self
-            .rows()
+        let rounded_rocks = self.rounded_rocks
             .iter()
-            .map(|row| tilt(row, west))
-            .collect()
-        )
+            .zip(&self.cube_rocks)
+            .map(|(rounded_rocks, cube_rocks)| {
+                let mut rounded_rocks = *rounded_rocks;
+
+                loop {
+                    let free = rounded_rocks.union(cube_rocks).complement();
+
+                    let moving = match west {
+                        true => rounded_rocks.intersection(&free.shifted_up(1)),
+                        false => rounded_rocks.intersection(&free.shifted_down(1)),
+                    };
+
+                    if moving.is_empty() {
+                        return rounded_rocks;
+                    }
+
+                    let moved = match west {
+                        true => moving.shifted_down(1),
+                        false => moving.shifted_up(1),
+                    };
+
+                    rounded_rocks = rounded_rocks.difference(&moving).union(&moved);
+                }
+            })
+            .collect();
+
+        Platform { rounded_rocks, cube_rocks: self.cube_rocks.clone() }
     }
 
     fn calculate_total_load(&self) -> u64 {
-        self.columns()
+        self.rounded_rocks
             .iter()
-            .map(|column| column.iter()
-                .rev()
-                .enumerate()
-                .filter(|(_, c)| **c == 'O')
-                .map(|(n, _)| n as u64 + 1)
-                .sum::<u64>()
-            )
+            .rev()
+            .enumerate()
+            .map(|(n, row)| (n as u64 + 1) * row.len() as u64)
             .sum()
     }
 
-    fn fingerprint(&self) -> String {
-        self.rows.iter().flatten().collect::<String>()
+    fn fingerprint(&self) -> Vec<Row> {
+        self.rounded_rocks.clone()
     }
 }
 
-fn platform_from_lines(lines: &Vec<String>) -> Platform {
-    platform_from_rows(lines.iter().map(|line| line.chars().collect::<Vec<char>>()).collect())
-}
+// every step is one spin cycle, reporting the total load afterwards
+impl Simulation for Platform {
+    type Event = u64;
 
-fn platform_from_rows(rows: Vec<Vec<char>>) -> Platform {
-    Platform { rows }
+    fn step(&mut self) -> Option<Vec<Self::Event>> {
+        *self = self.cycle();
+        Some(vec![self.calculate_total_load()])
+    }
 }
 
-fn platform_from_columns(columns: Vec<Vec<char>>) -> Platform {
-    platform_from_rows(
-        (0..columns.first().unwrap().len())
-            .map(|y| columns
-                .iter()
-                .map(|column| *(column.iter().nth(y).unwrap()))
-                .collect::<Vec<char>>()
+impl Frame for Platform {
+    fn render(&self) -> Vec<Vec<Cell>> {
+        self.rounded_rocks
+            .iter()
+            .zip(&self.cube_rocks)
+            .map(|(rounded_rocks, cube_rocks)| (0..rounded_rocks.width())
+                .map(|x| match () {
+                    _ if rounded_rocks.contains(x) => Cell::colored('O', Color::Yellow),
+                    _ if cube_rocks.contains(x) => Cell::colored('#', Color::Gray),
+                    _ => Cell::plain('.'),
+                })
+                .collect()
             )
             .collect()
-    )
+    }
 }
 
-fn tilt(line: &Vec<char>, reverse: bool) -> Vec<char> {
-    let mut parts = line
-        .split(|c| *c == '#')
-        .map(|part| part.iter().map(|c| *c).collect::<Vec<char>>())
-        .collect::<Vec<Vec<char>>>();
+// rows of up to 256 columns
+type Row = BitSet<u128, 2>;
 
-    parts.iter_mut().for_each(|part| {
-        part.sort_unstable();
-
-        if reverse {
-            part.reverse();
-        }
-    });
+fn platform_from_lines(lines: &Vec<String>) -> Result<Platform, String> {
+    let parse_rocks = |rock: char| lines.iter()
+        .map(|line| Row::try_from_bools(line.chars().map(|c| c == rock)))
+        .collect::<Result<Vec<Row>, String>>();
 
-    parts.join(&'#')
+    Ok(Platform { rounded_rocks: parse_rocks('O')?, cube_rocks: parse_rocks('#')? })
 }
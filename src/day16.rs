@@ -2,6 +2,8 @@ use std::collections::{HashSet};
 use std::fs::File;
 use std::hash::Hash;
 use std::io::{BufRead, BufReader};
+use crate::animation::{animate, AnimationOptions, Cell, Color, Frame};
+use crate::simulation::Simulation;
 
 #[allow(dead_code)]
 pub fn main() {
@@ -16,6 +18,21 @@ pub fn main() {
     println!("PART 2: {}", part2(&lines));
 }
 
+#[allow(dead_code)]
+pub fn animation() {
+    let input_file = File::open("inputs/16.txt").unwrap();
+
+    let lines = BufReader::new(input_file)
+        .lines()
+        .map(|line| line.unwrap())
+        .collect::<Vec<String>>();
+
+    let contraption = parse_contraption(&lines);
+    let beam = Beam::new(&contraption, Ray { direction: RayDirection::RIGHT, x: 0, y: 0 });
+
+    animate(beam, &AnimationOptions::from_args()).unwrap();
+}
+
 fn part1(lines: &Vec<String>) -> usize {
     parse_contraption(lines)
         .calculate_energized_tile_count(Ray { direction: RayDirection::RIGHT, x: 0, y: 0 })
@@ -49,25 +66,10 @@ struct Contraption {
 
 impl Contraption {
     fn calculate_energized_tile_count(&self, start_ray: Ray) -> usize {
-        let mut energized_tiles = HashSet::new();
-
-        let mut rays_cache = HashSet::new();
-        let mut rays = vec![start_ray];
-        while !rays.is_empty() {
-            let ray = rays.pop().unwrap();
-
-            if rays_cache.contains(&ray) {
-                continue;
-            }
-
-            energized_tiles.insert((ray.x, ray.y));
-            rays_cache.insert(ray);
-
-            let mut new_rays = self.simulate_ray(ray);
-            rays.append(&mut new_rays);
-        }
+        let mut beam = Beam::new(self, start_ray);
+        while beam.step().is_some() {}
 
-        energized_tiles.len()
+        beam.energized_tiles.len()
     }
 
     fn simulate_ray(&self, ray: Ray) -> Vec<Ray> {
@@ -91,6 +93,16 @@ enum Tile {
 }
 
 impl Tile {
+    fn symbol(&self) -> char {
+        match self {
+            Tile::Empty => '.',
+            Tile::LeftUpMirror => '/',
+            Tile::LeftDownMirror => '\\',
+            Tile::HorizontalSplitter => '-',
+            Tile::VerticalSplitter => '|',
+        }
+    }
+
     fn simulate_ray(&self, ray: Ray) -> Vec<Ray> {
         match self {
             Tile::Empty => {
@@ -139,6 +151,82 @@ impl Tile {
     }
 }
 
+// all rays of a beam advance by one tile per step, reporting the tiles that became energized
+#[derive(Clone)]
+struct Beam<'a> {
+    contraption: &'a Contraption,
+    rays: Vec<Ray>,
+    rays_cache: HashSet<Ray>,
+    energized_tiles: HashSet<(i32, i32)>,
+}
+
+impl Beam<'_> {
+    fn new(contraption: &Contraption, start_ray: Ray) -> Beam<'_> {
+        Beam {
+            contraption,
+            rays: vec![start_ray],
+            rays_cache: HashSet::new(),
+            energized_tiles: HashSet::new(),
+        }
+    }
+}
+
+impl Simulation for Beam<'_> {
+    type Event = (i32, i32);
+
+    fn step(&mut self) -> Option<Vec<Self::Event>> {
+        if self.rays.is_empty() {
+            return None;
+        }
+
+        let mut events = vec![];
+        let mut new_rays = vec![];
+
+        for ray in std::mem::take(&mut self.rays) {
+            if !self.rays_cache.insert(ray) {
+                continue;
+            }
+
+            if self.energized_tiles.insert((ray.x, ray.y)) {
+                events.push((ray.x, ray.y));
+            }
+
+            new_rays.append(&mut self.contraption.simulate_ray(ray));
+        }
+
+        self.rays = new_rays;
+        Some(events)
+    }
+}
+
+// energized tiles are highlighted, the heads of the rays are drawn as arrows
+impl Frame for Beam<'_> {
+    fn render(&self) -> Vec<Vec<Cell>> {
+        let mut frame = self.contraption.tiles
+            .iter()
+            .enumerate()
+            .map(|(y, row)| row.iter()
+                .enumerate()
+                .map(|(x, tile)| match self.energized_tiles.contains(&(x as i32, y as i32)) {
+                    true => Cell::colored(tile.symbol(), Color::Yellow),
+                    false => Cell::colored(tile.symbol(), Color::Gray),
+                })
+                .collect::<Vec<Cell>>()
+            )
+            .collect::<Vec<Vec<Cell>>>();
+
+        for ray in &self.rays {
+            frame[ray.y as usize][ray.x as usize] = Cell::colored(ray.direction.symbol(), Color::Red);
+        }
+
+        frame
+    }
+
+    fn focus(&self) -> Option<(usize, usize)> {
+        self.rays.first().map(|ray| (ray.x as usize, ray.y as usize))
+    }
+}
+
 #[derive(Copy, Clone, Eq, PartialEq, Hash)]
 struct Ray {
     direction: RayDirection,
@@ -154,6 +242,17 @@ enum RayDirection {
     RIGHT,
 }
 
+impl RayDirection {
+    fn symbol(&self) -> char {
+        match self {
+            RayDirection::UP => '^',
+            RayDirection::DOWN => 'v',
+            RayDirection::LEFT => '<',
+            RayDirection::RIGHT => '>',
+        }
+    }
+}
+
 fn parse_contraption(lines: &Vec<String>) -> Contraption {
     let tiles = lines.iter()
         .map(|line| line.chars()
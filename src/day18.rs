@@ -1,7 +1,7 @@
-use std::collections::{HashSet};
 use std::fs::File;
 use std::io::{BufRead, BufReader};
 use std::str::FromStr;
+use crate::geometry::{boundary_points, interior_points};
 
 #[allow(dead_code)]
 pub fn main() {
@@ -15,36 +15,21 @@ pub fn main() {
     println!("PART 1: {}", part1(&lines));
 }
 
-fn part1(lines: &Vec<String>) -> usize {
+fn part1(lines: &Vec<String>) -> i64 {
     let instructions = lines.iter()
         .map(|line| Instruction::from_str(line).unwrap())
         .collect::<Vec<Instruction>>();
 
-    let mut dug_tiles = HashSet::new();
+    let mut corners = vec![];
 
     let mut current_position = (0, 0);
     for instruction in instructions {
-        let tiles = instruction.get_tiles(current_position);
-
-        for tile in &tiles {
-            dug_tiles.insert(*tile);
-        }
-
-        current_position = *(tiles.last().unwrap());
+        current_position = instruction.get_end(current_position);
+        corners.push(current_position);
     }
 
-    let mut queue = vec![(1, 1)];
-    while let Some((x, y)) = queue.pop() {
-        if dug_tiles.contains(&(x, y)) {
-            continue;
-        }
-
-        dug_tiles.insert((x, y));
-
-        queue.append(&mut vec![(x - 1, y), (x + 1, y), (x, y - 1), (x, y + 1)]);
-    }
-
-    dug_tiles.len()
+    // the trench itself runs along the boundary, the lagoon also covers everything inside of it
+    interior_points(&corners) + boundary_points(&corners)
 }
 
 struct Instruction {
@@ -66,15 +51,13 @@ impl FromStr for Instruction {
 }
 
 impl Instruction {
-    fn get_tiles(&self, (x, y): (i64, i64)) -> Vec<(i64, i64)> {
-        (1..=self.length).map(|delta| {
-            match self.direction {
-                Direction::UP => (x, y - delta),
-                Direction::DOWN => (x, y + delta),
-                Direction::LEFT => (x - delta, y),
-                Direction::RIGHT => (x + delta, y)
-            }
-        }).collect()
+    fn get_end(&self, (x, y): (i64, i64)) -> (i64, i64) {
+        match self.direction {
+            Direction::UP => (x, y - self.length),
+            Direction::DOWN => (x, y + self.length),
+            Direction::LEFT => (x - self.length, y),
+            Direction::RIGHT => (x + self.length, y)
+        }
     }
 }
 
@@ -4,6 +4,7 @@ use std::fs::File;
 use std::io::{BufRead, BufReader};
 use std::str::FromStr;
 use regex::Regex;
+use crate::interner::Interner;
 
 #[allow(dead_code)]
 pub fn main() {
@@ -16,26 +17,53 @@ pub fn main() {
 
     let input_split = lines.split(|line| line.is_empty()).collect::<Vec<&[String]>>();
 
-    let workflows = input_split.get(0).unwrap().iter()
-        .map(|line| Workflow::from_str(line).unwrap())
-        .fold(HashMap::new(), |mut map, workflow| {
-            map.insert(workflow.name.clone(), workflow);
-            map
-        });
+    let (workflows, start_workflow) = parse_workflows(input_split.get(0).unwrap()).unwrap();
 
     let parts = input_split.get(1).unwrap().iter()
         .map(|line| Part::from_str(line).unwrap())
         .collect::<Vec<Part>>();
 
-    println!("PART 1: {}", part1(&workflows, &parts));
-    println!("PART 2: {}", part2(&workflows));
+    println!("PART 1: {}", part1(&workflows, start_workflow, &parts));
+    println!("PART 2: {}", part2(&workflows, start_workflow));
 }
 
-fn part1(workflows: &HashMap<String, Workflow>, parts: &Vec<Part>) -> u32 {
+// the workflows indexed by their id, and the id of "in"
+fn parse_workflows(lines: &[String]) -> Result<(Vec<Workflow>, usize), String> {
+    let mut workflow_names = Interner::new();
+    let mut workflows = lines.iter()
+        .map(|line| Workflow::parse(line, &mut workflow_names))
+        .collect::<Vec<Workflow>>();
+    workflows.sort_by_key(|workflow| workflow.id);
+
+    // rules can jump to names that are never defined, which leaves holes in the ids
+    let mut defined = vec![false; workflow_names.len()];
+    for workflow in &workflows {
+        if defined[workflow.id] {
+            return Err(format!("workflow \"{}\" is defined twice", workflow_names.name(workflow.id)));
+        }
+
+        defined[workflow.id] = true;
+    }
+
+    let undefined = workflow_names.names()
+        .filter(|(id, _)| !defined[*id])
+        .map(|(_, name)| format!("\"{}\"", name))
+        .collect::<Vec<String>>();
+
+    if !undefined.is_empty() {
+        return Err(format!("undefined workflows: {}", undefined.join(", ")));
+    }
+
+    let start_workflow = workflow_names.get("in").ok_or("there is no workflow \"in\"")?;
+
+    Ok((workflows, start_workflow))
+}
+
+fn part1(workflows: &[Workflow], start_workflow: usize, parts: &Vec<Part>) -> u32 {
     parts
         .iter()
         .filter(|part| {
-            let mut current_workflow = workflows.get("in").unwrap();
+            let mut current_workflow = &workflows[start_workflow];
 
             loop {
                 match current_workflow.get_action_for_part(&part) {
@@ -45,8 +73,8 @@ fn part1(workflows: &HashMap<String, Workflow>, parts: &Vec<Part>) -> u32 {
                     RuleAction::REJECT => {
                         return false;
                     }
-                    RuleAction::JUMP(workflow_name) => {
-                        current_workflow = workflows.get(workflow_name.as_str()).unwrap();
+                    RuleAction::JUMP(workflow_id) => {
+                        current_workflow = &workflows[workflow_id];
                     }
                 }
             }
@@ -55,8 +83,8 @@ fn part1(workflows: &HashMap<String, Workflow>, parts: &Vec<Part>) -> u32 {
         .sum()
 }
 
-fn part2(workflows: &HashMap<String, Workflow>) -> u64 {
-    find_all_accepting_ranges(&workflows, "in", RatingRange::new())
+fn part2(workflows: &[Workflow], start_workflow: usize) -> u64 {
+    find_all_accepting_ranges(&workflows, start_workflow, RatingRange::new())
         .iter()
         .filter(|range| range.is_possible())
         .map(|range| range.ranges
@@ -68,11 +96,11 @@ fn part2(workflows: &HashMap<String, Workflow>) -> u64 {
 }
 
 fn find_all_accepting_ranges(
-    workflows: &HashMap<String, Workflow>,
-    current_workflow_name: &str,
+    workflows: &[Workflow],
+    current_workflow_id: usize,
     mut current_range: RatingRange,
 ) -> Vec<RatingRange> {
-    let workflow = workflows.get(current_workflow_name).unwrap();
+    let workflow = &workflows[current_workflow_id];
 
     let mut ranges = vec![];
 
@@ -82,8 +110,8 @@ fn find_all_accepting_ranges(
                 ranges.push(current_range.with_rule_applied(rule));
             }
             RuleAction::REJECT => {}
-            RuleAction::JUMP(workflow_name) => {
-                ranges.append(&mut find_all_accepting_ranges(workflows, workflow_name, current_range.with_rule_applied(rule)));
+            RuleAction::JUMP(workflow_id) => {
+                ranges.append(&mut find_all_accepting_ranges(workflows, *workflow_id, current_range.with_rule_applied(rule)));
             }
         }
 
@@ -95,8 +123,8 @@ fn find_all_accepting_ranges(
             ranges.push(current_range);
         }
         RuleAction::REJECT => {}
-        RuleAction::JUMP(workflow_name) => {
-            ranges.append(&mut find_all_accepting_ranges(workflows, workflow_name, current_range));
+        RuleAction::JUMP(workflow_id) => {
+            ranges.append(&mut find_all_accepting_ranges(workflows, *workflow_id, current_range));
         }
     }
 
@@ -104,37 +132,33 @@ fn find_all_accepting_ranges(
 }
 
 struct Workflow {
-    name: String,
+    id: usize,
     rules: Vec<Rule>,
     default_action: RuleAction,
 }
 
-impl FromStr for Workflow {
-    type Err = ();
-
-    fn from_str(s: &str) -> Result<Self, Self::Err> {
+impl Workflow {
+    fn parse(s: &str, workflow_names: &mut Interner) -> Workflow {
         let regex = Regex::new(r"^([a-z]+)\{(.*)}$").unwrap();
         let groups = regex.captures(s).unwrap();
 
-        let name = groups.get(1).unwrap().as_str().to_string();
+        let id = workflow_names.intern(groups.get(1).unwrap().as_str());
         let raw_rules = groups.get(2).unwrap().as_str().split(",").collect::<Vec<&str>>();
 
         let rules = raw_rules.iter().take(raw_rules.len() - 1)
-            .map(|s| Rule::from_str(s).unwrap()).collect::<Vec<Rule>>();
+            .map(|s| Rule::parse(s, workflow_names)).collect::<Vec<Rule>>();
 
-        Ok(Workflow {
-            name: name.to_string(),
+        Workflow {
+            id,
             rules,
-            default_action: RuleAction::from_str(raw_rules.last().unwrap()).unwrap(),
-        })
+            default_action: RuleAction::parse(raw_rules.last().unwrap(), workflow_names),
+        }
     }
-}
 
-impl Workflow {
     fn get_action_for_part(&self, part: &Part) -> RuleAction {
         self.rules.iter()
             .find_map(|rule| rule.apply_for_part(&part))
-            .unwrap_or(self.default_action.clone())
+            .unwrap_or(self.default_action)
     }
 }
 
@@ -146,23 +170,19 @@ struct Rule {
     action: RuleAction,
 }
 
-impl FromStr for Rule {
-    type Err = ();
-
-    fn from_str(s: &str) -> Result<Self, Self::Err> {
+impl Rule {
+    fn parse(s: &str, workflow_names: &mut Interner) -> Rule {
         let regex = Regex::new(r"^([xmas])([><])([0-9]+):([a-zA-Z]+)$").unwrap();
         let groups = regex.captures(s).unwrap();
 
         let rating_type = RatingType::from_str(groups.get(1).unwrap().as_str()).unwrap();
         let condition = RuleCondition::from_str(groups.get(2).unwrap().as_str()).unwrap();
         let value = groups.get(3).unwrap().as_str().parse::<u32>().unwrap();
-        let action = RuleAction::from_str(groups.get(4).unwrap().as_str()).unwrap();
+        let action = RuleAction::parse(groups.get(4).unwrap().as_str(), workflow_names);
 
-        Ok(Rule { rating_type, condition, value, action })
+        Rule { rating_type, condition, value, action }
     }
-}
 
-impl Rule {
     fn apply_for_part(&self, part: &Part) -> Option<RuleAction> {
         let rating = *part.ratings.get(&self.rating_type).unwrap();
 
@@ -175,7 +195,7 @@ impl Rule {
             return None;
         }
 
-        Some(self.action.clone())
+        Some(self.action)
     }
 }
 
@@ -219,21 +239,19 @@ impl FromStr for RuleCondition {
     }
 }
 
-#[derive(Clone, Eq, PartialEq)]
+#[derive(Copy, Clone, Eq, PartialEq)]
 enum RuleAction {
     ACCEPT,
     REJECT,
-    JUMP(String),
+    JUMP(usize),
 }
 
-impl FromStr for RuleAction {
-    type Err = ();
-
-    fn from_str(s: &str) -> Result<Self, Self::Err> {
+impl RuleAction {
+    fn parse(s: &str, workflow_names: &mut Interner) -> RuleAction {
         match s {
-            "A" => Ok(RuleAction::ACCEPT),
-            "R" => Ok(RuleAction::REJECT),
-            &_ => Ok(RuleAction::JUMP(s.to_string())),
+            "A" => RuleAction::ACCEPT,
+            "R" => RuleAction::REJECT,
+            &_ => RuleAction::JUMP(workflow_names.intern(s)),
         }
     }
 }
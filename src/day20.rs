@@ -1,7 +1,8 @@
 use std::collections::{HashMap, VecDeque};
 use std::fs::File;
 use std::io::{BufRead, BufReader};
-use std::str::FromStr;
+use crate::interner::Interner;
+use crate::simulation::Simulation;
 
 #[allow(dead_code)]
 pub fn main() {
@@ -16,71 +17,106 @@ pub fn main() {
 }
 
 fn part1(lines: &Vec<String>) -> usize {
-    let mut modules = lines
-        .iter()
-        .map(|line| Module::from_str(line).unwrap())
-        .fold(HashMap::new(), |mut map, module| {
-            map.insert(module.name.to_string(), module);
-            map
-        });
-
-    let inputs_per_module = modules.values()
-        .fold(HashMap::new(), |mut map, module| {
-            let inputs = modules.values()
-                .filter(|other_module| other_module.outputs.contains(&module.name))
-                .map(|other_module| other_module.name.to_string())
-                .collect::<Vec<String>>();
-
-            map.insert(module.name.to_string(), inputs);
-            map
-        });
-
-    for module in modules.values_mut() {
-        if let ModuleType::Conjunction(inputs) = &mut module.module_type {
-            for input_module_name in inputs_per_module.get(&module.name).unwrap() {
-                inputs.insert(input_module_name.to_string(), PulseType::LOW);
+    // the network is stepped directly, a simulator would keep a snapshot and every pulse of each press
+    let mut network = ModuleNetwork::from(lines);
+    let mut low_pulses = 0;
+    let mut high_pulses = 0;
+
+    while network.button_presses < 1000 {
+        for pulse in network.step().unwrap() {
+            match pulse.pulse_type {
+                PulseType::LOW => low_pulses += 1,
+                PulseType::HIGH => high_pulses += 1,
             }
         }
     }
 
-    //
+    low_pulses * high_pulses
+}
+
+#[derive(Clone)]
+struct ModuleNetwork {
+    // modules that only appear as outputs (like "rx") stay None
+    modules: Vec<Option<Module>>,
+    button: usize,
+    broadcaster: usize,
+    button_presses: usize,
+}
+
+impl From<&Vec<String>> for ModuleNetwork {
+    fn from(lines: &Vec<String>) -> Self {
+        let mut module_names = Interner::new();
+        let button = module_names.intern("button");
+        let broadcaster = module_names.intern("broadcaster");
+
+        let parsed_modules = lines
+            .iter()
+            .map(|line| Module::parse(line, &mut module_names))
+            .collect::<Vec<Module>>();
+
+        let mut modules = (0..module_names.len()).map(|_| None).collect::<Vec<Option<Module>>>();
+        let mut inputs_per_module = vec![vec![]; module_names.len()];
+
+        for module in parsed_modules {
+            for output in &module.outputs {
+                inputs_per_module[*output].push(module.id);
+            }
+
+            let id = module.id;
+            modules[id] = Some(module);
+        }
+
+        for module in modules.iter_mut().flatten() {
+            if let ModuleType::Conjunction(inputs) = &mut module.module_type {
+                for input_module in &inputs_per_module[module.id] {
+                    inputs.insert(*input_module, PulseType::LOW);
+                }
+            }
+        }
 
-    let mut pulse_counts = HashMap::new();
-    pulse_counts.insert(PulseType::LOW, 0);
-    pulse_counts.insert(PulseType::HIGH, 0);
+        ModuleNetwork { modules, button, broadcaster, button_presses: 0 }
+    }
+}
 
-    for _ in 0..1000 {
+// every step is one press of the button, reporting all pulses sent until the network is idle again
+impl Simulation for ModuleNetwork {
+    type Event = Pulse;
+
+    fn step(&mut self) -> Option<Vec<Self::Event>> {
+        let mut pulses = vec![];
         let mut queue = VecDeque::new();
 
-        queue.push_back(Pulse { from: "".to_string(), to: "broadcaster".to_string(), pulse_type: PulseType::LOW });
-        pulse_counts.entry(PulseType::LOW).and_modify(|count| *count += 1);
+        queue.push_back(Pulse { from: self.button, to: self.broadcaster, pulse_type: PulseType::LOW });
+        self.button_presses += 1;
 
         while let Some(pulse) = queue.pop_front() {
-            if !modules.contains_key(&pulse.to) {
+            pulses.push(pulse);
+
+            let Some(module) = &mut self.modules[pulse.to] else {
                 continue;
-            }
+            };
 
-            let module = modules.get_mut(&pulse.to).unwrap();
             for new_pulse in module.simulate_pulse(&pulse) {
-                pulse_counts.entry(new_pulse.pulse_type).and_modify(|count| *count += 1);
                 queue.push_back(new_pulse);
             }
         }
-    }
 
-    pulse_counts.values().fold(1, |acc, n| acc * n)
+        Some(pulses)
+    }
 }
 
+#[derive(Clone)]
 struct Module {
-    name: String,
-    outputs: Vec<String>,
+    id: usize,
+    outputs: Vec<usize>,
     module_type: ModuleType,
 }
 
+#[derive(Clone)]
 enum ModuleType {
     Broadcast,
     FlipFlop(FlipFlowStatus),
-    Conjunction(HashMap<String, PulseType>),
+    Conjunction(HashMap<usize, PulseType>),
 }
 
 #[derive(Copy, Clone, Eq, PartialEq, Hash)]
@@ -89,14 +125,12 @@ enum PulseType {
     HIGH,
 }
 
-impl FromStr for Module {
-    type Err = ();
-
-    fn from_str(s: &str) -> Result<Self, Self::Err> {
+impl Module {
+    fn parse(s: &str, module_names: &mut Interner) -> Module {
         let split = s.split(" -> ").collect::<Vec<&str>>();
         let left = split.get(0).unwrap();
         let outputs = split.get(1).unwrap().split(", ")
-            .map(|output| output.to_string()).collect::<Vec<String>>();
+            .map(|output| module_names.intern(output)).collect::<Vec<usize>>();
 
         let module_type = match () {
             _ if left.starts_with("%") => ModuleType::FlipFlop(FlipFlowStatus::OFF),
@@ -104,15 +138,13 @@ impl FromStr for Module {
             _ => ModuleType::Broadcast
         };
 
-        Ok(Module {
-            name: left.trim_matches(|c| c == '%' || c == '&').to_string(),
+        Module {
+            id: module_names.intern(left.trim_matches(|c| c == '%' || c == '&')),
             outputs,
             module_type,
-        })
+        }
     }
-}
 
-impl Module {
     fn simulate_pulse(&mut self, pulse: &Pulse) -> Vec<Pulse> {
         let output_pulse_type = match &mut self.module_type {
             ModuleType::Broadcast => {
@@ -134,7 +166,7 @@ impl Module {
                 }
             }
             ModuleType::Conjunction(memory) => {
-                memory.insert(pulse.from.to_string(), pulse.pulse_type);
+                memory.insert(pulse.from, pulse.pulse_type);
 
                 if memory.values().all(|pulse_type| *pulse_type == PulseType::HIGH) {
                     Some(PulseType::LOW)
@@ -150,8 +182,8 @@ impl Module {
                 self.outputs
                     .iter()
                     .map(|output| Pulse {
-                        from: self.name.to_string(),
-                        to: output.to_string(),
+                        from: self.id,
+                        to: *output,
                         pulse_type,
                     })
                     .collect()
@@ -174,9 +206,9 @@ impl FlipFlowStatus {
     }
 }
 
-
+#[derive(Copy, Clone)]
 struct Pulse {
-    from: String,
-    to: String,
+    from: usize,
+    to: usize,
     pulse_type: PulseType,
 }
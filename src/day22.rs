@@ -3,6 +3,8 @@ use std::fs::File;
 use std::io::{BufRead, BufReader};
 use std::str::FromStr;
 use rayon::prelude::*;
+use crate::animation::{animate, AnimationOptions, Cell, Color, Frame};
+use crate::simulation::Simulation;
 
 #[allow(dead_code)]
 pub fn main() {
@@ -17,6 +19,18 @@ pub fn main() {
     println!("PART 2: {}", part2(&lines));
 }
 
+#[allow(dead_code)]
+pub fn animation() {
+    let input_file = File::open("inputs/22.txt").unwrap();
+
+    let lines = BufReader::new(input_file)
+        .lines()
+        .map(|line| line.unwrap())
+        .collect::<Vec<String>>();
+
+    animate(BrickPile::new(&lines), &AnimationOptions::from_args()).unwrap();
+}
+
 fn part1(lines: &Vec<String>) -> usize {
     let mut brick_pile = BrickPile::new(lines);
     brick_pile.simulate();
@@ -47,16 +61,11 @@ fn part2(lines: &Vec<String>) -> usize {
             let mut modified_pile = brick_pile.clone();
             modified_pile.bricks.remove(i);
 
-            modified_pile.simulate();
+            let moved_bricks = std::iter::from_fn(|| modified_pile.step())
+                .flatten()
+                .collect::<HashSet<usize>>();
 
-            let moved_brick_count = modified_pile.bricks.iter()
-                .filter(|brick| {
-                    let original_brick = brick_pile.bricks.iter().find(|b| b.id == brick.id).unwrap();
-                    original_brick.cubes != brick.cubes
-                })
-                .count();
-
-            moved_brick_count
+            moved_bricks.len()
         })
         .sum()
 }
@@ -79,17 +88,58 @@ impl BrickPile {
     }
 
     fn simulate(&mut self) {
-        let mut changed = true;
-        while changed {
-            changed = false;
-
-            for i in 0..self.bricks.len() {
-                while !self.bricks[i].is_on_ground() && !self.bricks[i].is_supported(&self.bricks) {
-                    self.bricks[i].move_one_down();
-                    changed = true;
-                }
+        while self.step().is_some() {}
+    }
+}
+
+// every step lets each brick fall as far as it currently can, reporting the id of a brick
+// once for every level it fell
+impl Simulation for BrickPile {
+    type Event = usize;
+
+    fn step(&mut self) -> Option<Vec<Self::Event>> {
+        let mut moved_bricks = vec![];
+
+        for i in 0..self.bricks.len() {
+            while !self.bricks[i].is_on_ground() && !self.bricks[i].is_supported(&self.bricks) {
+                self.bricks[i].move_one_down();
+                moved_bricks.push(self.bricks[i].id);
             }
         }
+
+        if moved_bricks.is_empty() {
+            return None;
+        }
+
+        Some(moved_bricks)
+    }
+}
+
+// side view onto the x/z plane with the ground at the bottom, every brick only hides
+// the bricks behind it (with a larger y)
+impl Frame for BrickPile {
+    fn render(&self) -> Vec<Vec<Cell>> {
+        let cubes = self.bricks.iter()
+            .flat_map(|brick| brick.cubes.iter().map(|cube| (*cube, brick.id)))
+            .collect::<Vec<_>>();
+
+        let width = cubes.iter().map(|((x, _, _), _)| *x as usize + 1).max().unwrap_or(0);
+        let height = cubes.iter().map(|((_, _, z), _)| *z as usize).max().unwrap_or(0);
+
+        let mut frame = vec![vec![Cell::plain('.'); width]; height];
+        let mut depths = vec![vec![i32::MAX; width]; height];
+
+        for ((x, y, z), id) in cubes {
+            let (row, column) = (height - z as usize, x as usize);
+
+            if y < depths[row][column] {
+                depths[row][column] = y;
+                frame[row][column] = Cell::colored((b'A' + (id % 26) as u8) as char, Color::PALETTE[id % Color::PALETTE.len()]);
+            }
+        }
+
+        frame.push(vec![Cell::colored('-', Color::Gray); width]);
+        frame
     }
 }
 
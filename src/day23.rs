@@ -3,6 +3,7 @@ use std::collections::{HashMap, HashSet};
 use std::fs::File;
 use std::io::{BufRead, BufReader};
 use rayon::prelude::*;
+use crate::bitset::BitSet;
 
 #[allow(dead_code)]
 pub fn main() {
@@ -14,7 +15,10 @@ pub fn main() {
         .collect::<Vec<String>>();
 
     println!("PART 1: {}", part1(&lines));
-    println!("PART 2: {}", part2(&lines));
+    match part2(&lines) {
+        Ok(distance) => println!("PART 2: {}", distance),
+        Err(error) => println!("PART 2: {}", error),
+    }
 }
 
 fn part1(lines: &Vec<String>) -> usize {
@@ -59,13 +63,21 @@ fn part1(lines: &Vec<String>) -> usize {
     max_path_length
 }
 
-fn part2(lines: &Vec<String>) -> usize {
+fn part2(lines: &Vec<String>) -> Result<usize, String> {
     let map = Map::new(lines);
     let graph = map.compress_to_graph();
 
-    graph.find_longest_path_distance((map.start_x, 0), (map.end_x, map.height - 1), HashSet::new()).unwrap()
+    let start = *graph.junctions.get(&(map.start_x, 0)).unwrap();
+    let end = *graph.junctions.get(&(map.end_x, map.height - 1)).unwrap();
+
+    let path = Path::try_new(graph.edges.len()).map_err(|_| format!("more than {} junctions", Path::CAPACITY))?;
+
+    graph.find_longest_path_distance(start, end, path).ok_or("there is no path to the end".to_string())
 }
 
+// the junctions visited so far, copied into every recursive call
+type Path = BitSet<u64, 4>;
+
 struct Map {
     tiles: Vec<Vec<char>>,
     width: i32,
@@ -98,7 +110,7 @@ impl Map {
     }
 
     fn compress_to_graph(&self) -> Graph {
-        let mut graph = Graph { edges: HashMap::new() };
+        let mut graph = Graph { junctions: HashMap::new(), edges: vec![] };
 
         let mut visited_junctions = HashSet::new();
         let mut junction_queue = vec![(self.start_x, 0)];
@@ -164,35 +176,43 @@ impl Map {
 }
 
 struct Graph {
-    edges: HashMap<(i32, i32), HashMap<(i32, i32), usize>>,
+    // dense ids of the junction positions, used to index the edges
+    junctions: HashMap<(i32, i32), usize>,
+    edges: Vec<Vec<(usize, usize)>>,
 }
 
 impl Graph {
+    fn get_junction_id(&mut self, position: (i32, i32)) -> usize {
+        let next_id = self.junctions.len();
+        let id = *self.junctions.entry(position).or_insert(next_id);
+
+        if id == next_id {
+            self.edges.push(vec![]);
+        }
+
+        id
+    }
+
     fn update_distance(&mut self, from: (i32, i32), to: (i32, i32), distance: usize) {
-        self.edges.entry(from)
-            .and_modify(|connections| {
-                connections.entry(to)
-                    .and_modify(|curr_distance| *curr_distance = max(distance, *curr_distance))
-                    .or_insert(distance);
-            })
-            .or_insert(HashMap::from([(to, distance)]));
+        let from = self.get_junction_id(from);
+        let to = self.get_junction_id(to);
+
+        match self.edges[from].iter_mut().find(|(next, _)| *next == to) {
+            Some((_, curr_distance)) => *curr_distance = max(distance, *curr_distance),
+            None => self.edges[from].push((to, distance)),
+        }
     }
 
-    fn find_longest_path_distance(&self, start: (i32, i32), end: (i32, i32), path: HashSet<(i32, i32)>) -> Option<usize> {
+    fn find_longest_path_distance(&self, start: usize, end: usize, path: Path) -> Option<usize> {
         if start == end {
             return Some(0);
         }
 
-        self.edges.get(&start).unwrap().into_par_iter()
-            .filter(|(next, _)| !path.contains(next))
+        self.edges[start].par_iter()
+            .filter(|(next, _)| !path.contains(*next))
             .filter_map(|(next, distance)| {
-                let mut new_path = path.clone();
-                new_path.insert(*next);
-
-                match self.find_longest_path_distance(*next, end, new_path) {
-                    None => None,
-                    Some(remaining_distance) => Some(distance + remaining_distance),
-                }
+                self.find_longest_path_distance(*next, end, path.with(*next))
+                    .map(|remaining_distance| distance + remaining_distance)
             })
             .max()
     }
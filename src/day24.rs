@@ -1,6 +1,8 @@
 use std::fs::File;
 use std::io::{BufRead, BufReader};
 use std::str::FromStr;
+use crate::geometry::{ray_intersection, Intersection, Line};
+use crate::rational::Rational;
 
 #[allow(dead_code)]
 pub fn main() {
@@ -19,20 +21,22 @@ fn part1(lines: &Vec<String>) -> usize {
         .map(|line| Hailstone::from_str(line).unwrap())
         .collect::<Vec<_>>();
 
-    const TEST_AREA_MIN: f64 = 200000000000000.0;
-    const TEST_AREA_MAX: f64 = 400000000000000.0;
+    let test_area_min = Rational::from(200000000000000);
+    let test_area_max = Rational::from(400000000000000);
 
     let mut count = 0;
 
     for i in 0..hailstones.len() {
         for j in (i + 1)..hailstones.len() {
-            let (s, t, (x, y, _)) = hailstones.get(i).unwrap().calculate_intersection(hailstones.get(j).unwrap());
+            let other = hailstones.get(j).unwrap();
 
-            if s < 0.0 || t < 0.0 {
+            let Intersection::Crossing { t, .. } = hailstones.get(i).unwrap().calculate_intersection(other) else {
                 continue;
-            }
+            };
+
+            let (x, y, _) = other.at(&t);
 
-            if x < TEST_AREA_MIN || TEST_AREA_MAX < x || y < TEST_AREA_MIN || TEST_AREA_MAX < y {
+            if x < test_area_min || test_area_max < x || y < test_area_min || test_area_max < y {
                 continue;
             }
 
@@ -44,29 +48,32 @@ fn part1(lines: &Vec<String>) -> usize {
 }
 
 struct Hailstone {
-    position: (f64, f64, f64),
-    velocity: (f64, f64, f64),
+    position: (i64, i64, i64),
+    velocity: (i64, i64, i64),
 }
 
 impl Hailstone {
-    fn calculate_intersection(&self, other: &Hailstone) -> (f64, f64, (f64, f64, f64)) {
+    // where the paths of both hailstones cross in the xy-plane, looking only forward in time
+    fn calculate_intersection(&self, other: &Hailstone) -> Intersection {
+        ray_intersection(&self.xy_path(), &other.xy_path())
+    }
+
+    fn xy_path(&self) -> Line {
         let (x, y, _) = self.position;
         let (dx, dy, _) = self.velocity;
 
-        let (a, b, _) = other.position;
-        let (da, db, _) = other.velocity;
-
-        let t = (b - y - (dy / dx) * (a - x)) / (dy * da / dx - db);
-        let s = (a - x + da * t) / dx;
-
-        (s, t, other.at(t))
+        Line { origin: (x, y), direction: (dx, dy) }
     }
 
-    fn at(&self, t: f64) -> (f64, f64, f64) {
+    fn at(&self, t: &Rational) -> (Rational, Rational, Rational) {
         let (x, y, z) = self.position;
         let (dx, dy, dz) = self.velocity;
 
-        (x + dx * t, y + dy * t, z + dz * t)
+        (
+            Rational::from(x) + Rational::from(dx) * t,
+            Rational::from(y) + Rational::from(dy) * t,
+            Rational::from(z) + Rational::from(dz) * t,
+        )
     }
 }
 
@@ -82,14 +89,14 @@ impl FromStr for Hailstone {
 
         Ok(Hailstone {
             position: (
-                *position.get(0).unwrap() as f64,
-                *position.get(1).unwrap() as f64,
-                *position.get(2).unwrap() as f64,
+                *position.get(0).unwrap(),
+                *position.get(1).unwrap(),
+                *position.get(2).unwrap(),
             ),
             velocity: (
-                *velocity.get(0).unwrap() as f64,
-                *velocity.get(1).unwrap() as f64,
-                *velocity.get(2).unwrap() as f64,
+                *velocity.get(0).unwrap(),
+                *velocity.get(1).unwrap(),
+                *velocity.get(2).unwrap(),
             ),
         })
     }
@@ -1,8 +1,9 @@
 use std::cmp::{min, Ordering};
-use std::collections::{BinaryHeap, HashMap, HashSet};
+use std::collections::{BinaryHeap, HashMap};
 use std::fs::File;
 use std::io::{BufRead, BufReader};
 use rayon::prelude::*;
+use crate::interner::Interner;
 
 #[allow(dead_code)]
 pub fn main() {
@@ -18,24 +19,22 @@ pub fn main() {
 
 fn part1(lines: &Vec<String>) -> usize {
     let graph = Graph::from(lines);
+    let node_count = graph.adjacency.len();
 
-    let local_edge_usages = (0..graph.nodes.len())
+    let local_edge_usages = (0..node_count)
         .into_par_iter()
-        .map(|i| {
+        .map(|start_node| {
             let mut local_edge_usage = HashMap::new();
 
-            let start_node = graph.nodes.get(i).unwrap();
             let shortest_paths = graph.find_shortest_paths_from_node(start_node);
 
-            for j in i + 1..graph.nodes.len() {
-                let mut current_node = graph.nodes.get(j).unwrap().as_str();
+            for end_node in start_node + 1..node_count {
+                let mut current_node = end_node;
 
                 while current_node != start_node {
-                    let predecessor = shortest_paths.get(current_node).unwrap();
+                    let predecessor = shortest_paths[current_node];
 
-                    let (from, to) = (predecessor.to_string(), current_node.to_string());
-
-                    local_edge_usage.entry(get_unique_edge(from, to))
+                    local_edge_usage.entry(get_unique_edge(predecessor, current_node))
                         .and_modify(|count| *count += 1)
                         .or_insert(1);
 
@@ -62,29 +61,28 @@ fn part1(lines: &Vec<String>) -> usize {
     entries.reverse();
 
     let edges_by_priority = entries.iter()
-        .map(|((from, to), _)| (from.to_string(), to.to_string()))
+        .map(|(edge, _)| **edge)
         .collect::<Vec<_>>();
-    let node_set = graph.nodes.clone().into_iter().collect::<HashSet<String>>();
 
     for i in 0..min(edges_by_priority.len(), 10) {
         for j in i + 1..min(edges_by_priority.len(), 10) {
             for k in j + 1..min(edges_by_priority.len(), 10) {
                 let modified_graph = graph
-                    .without_edge(edges_by_priority.get(i).unwrap())
-                    .without_edge(edges_by_priority.get(j).unwrap())
-                    .without_edge(edges_by_priority.get(k).unwrap());
-
-                let connected_nodes = modified_graph.find_connected_nodes(graph.nodes.first().unwrap());
-                let remaining_nodes = node_set.difference(&connected_nodes)
-                    .map(|node| node.to_string()).collect::<HashSet<String>>();
+                    .without_edge(edges_by_priority[i])
+                    .without_edge(edges_by_priority[j])
+                    .without_edge(edges_by_priority[k]);
 
-                if remaining_nodes.is_empty() {
+                let connected_nodes = modified_graph.find_connected_nodes(0);
+                let Some(remaining_node) = (0..node_count).find(|node| !connected_nodes[*node]) else {
                     continue;
-                }
+                };
 
-                let other_connected_nodes = modified_graph.find_connected_nodes(remaining_nodes.iter().next().unwrap());
-                if remaining_nodes.difference(&other_connected_nodes).count() == 0 {
-                    return connected_nodes.len() * other_connected_nodes.len();
+                let other_connected_nodes = modified_graph.find_connected_nodes(remaining_node);
+                let connected_count = connected_nodes.iter().filter(|connected| **connected).count();
+                let other_connected_count = other_connected_nodes.iter().filter(|connected| **connected).count();
+
+                if connected_count + other_connected_count == node_count {
+                    return connected_count * other_connected_count;
                 }
             }
         }
@@ -93,7 +91,7 @@ fn part1(lines: &Vec<String>) -> usize {
     0
 }
 
-fn get_unique_edge(from: String, to: String) -> (String, String) {
+fn get_unique_edge(from: usize, to: usize) -> (usize, usize) {
     if from > to {
         (to, from)
     } else {
@@ -102,121 +100,99 @@ fn get_unique_edge(from: String, to: String) -> (String, String) {
 }
 
 struct Graph {
-    nodes: Vec<String>,
-    edges: HashSet<(String, String)>,
+    adjacency: Vec<Vec<usize>>,
 }
 
 impl From<&Vec<String>> for Graph {
     fn from(lines: &Vec<String>) -> Self {
-        let mut nodes = HashSet::new();
-        let mut edges = HashSet::new();
+        let mut nodes = Interner::new();
+        let mut adjacency: Vec<Vec<usize>> = Vec::new();
 
         for line in lines {
             let mut split = line.split(": ").into_iter();
-            let from = split.next().unwrap();
+            let from = nodes.intern(split.next().unwrap());
             let to_nodes = split.last().unwrap().split(" ").into_iter();
 
-            nodes.insert(from.to_string());
-
             for to in to_nodes {
-                nodes.insert(to.to_string());
-                edges.insert(get_unique_edge(from.to_string(), to.to_string()));
+                let to = nodes.intern(to);
+                adjacency.resize(nodes.len(), vec![]);
+
+                adjacency[from].push(to);
+                adjacency[to].push(from);
             }
         }
 
-        Graph { nodes: nodes.into_iter().collect(), edges }
+        Graph { adjacency }
     }
 }
 
 impl Graph {
-    fn without_edge(&self, edge: &(String, String)) -> Graph {
-        let mut new_edges = self.edges.clone();
-        new_edges.remove(edge);
+    fn without_edge(&self, (from, to): (usize, usize)) -> Graph {
+        let mut adjacency = self.adjacency.clone();
+        adjacency[from].retain(|node| *node != to);
+        adjacency[to].retain(|node| *node != from);
 
-        Graph {
-            nodes: self.nodes.clone(),
-            edges: new_edges,
-        }
+        Graph { adjacency }
     }
 
-    fn get_neighbours(&self, node: &str) -> Vec<&str> {
-        self.edges.iter()
-            .filter_map(|(from, to)|
-                if from == node {
-                    Some(to.as_str())
-                } else if to == node {
-                    Some(from.as_str())
-                } else {
-                    None
-                }
-            )
-            .collect()
-    }
-
-    fn find_connected_nodes(&self, start_node: &str) -> HashSet<String> {
-        let mut connected_nodes = HashSet::new();
+    fn find_connected_nodes(&self, start_node: usize) -> Vec<bool> {
+        let mut connected_nodes = vec![false; self.adjacency.len()];
 
         let mut queue = Vec::from([start_node]);
         while let Some(node) = queue.pop() {
-            if connected_nodes.contains(node) {
+            if connected_nodes[node] {
                 continue;
             }
 
-            connected_nodes.insert(node.to_string());
+            connected_nodes[node] = true;
 
-            for neighbour in self.get_neighbours(node) {
-                queue.push(neighbour);
+            for neighbour in &self.adjacency[node] {
+                queue.push(*neighbour);
             }
         }
 
         connected_nodes
     }
 
-    fn find_shortest_paths_from_node<'a>(&'a self, start_node: &'a str) -> HashMap<&'a str, &'a str> {
+    fn find_shortest_paths_from_node(&self, start_node: usize) -> Vec<usize> {
         #[derive(Copy, Clone, Eq, PartialEq)]
-        struct State<'a> {
+        struct State {
             distance: u32,
-            node: &'a str,
+            node: usize,
         }
 
-        impl PartialOrd<Self> for State<'_> {
+        impl PartialOrd<Self> for State {
             fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
                 Some(self.cmp(other))
             }
         }
 
-        impl Ord for State<'_> {
+        impl Ord for State {
             fn cmp(&self, other: &Self) -> Ordering {
                 other.distance.cmp(&self.distance).then_with(|| self.node.cmp(&other.node))
             }
         }
 
         let mut heap = BinaryHeap::new();
-        let mut distances = HashMap::new();
-        let mut predecessors = HashMap::new();
+        let mut distances = vec![u32::MAX; self.adjacency.len()];
+        let mut predecessors = vec![start_node; self.adjacency.len()];
 
         heap.push(State { distance: 0, node: start_node });
-        distances.insert(start_node, 0);
-        predecessors.insert(start_node, "");
+        distances[start_node] = 0;
 
         while let Some(state) = heap.pop() {
-            if distances.contains_key(&state.node) && *distances.get(&state.node).unwrap() < state.distance {
+            if distances[state.node] < state.distance {
                 continue;
             }
 
-            for connected_node in self.get_neighbours(state.node) {
-                let connected_node_state = State { node: connected_node, distance: state.distance + 1 };
+            for connected_node in &self.adjacency[state.node] {
+                let connected_node_state = State { node: *connected_node, distance: state.distance + 1 };
 
-                if !distances.contains_key(connected_node) || *distances.get(connected_node).unwrap() > connected_node_state.distance {
+                if distances[*connected_node] > connected_node_state.distance {
                     heap.push(connected_node_state);
 
-                    distances.entry(connected_node)
-                        .and_modify(|distance| *distance = connected_node_state.distance)
-                        .or_insert(connected_node_state.distance);
-
-                    predecessors.entry(connected_node)
-                        .and_modify(|predecessor| *predecessor = state.node)
-                        .or_insert(state.node);
+                    distances[*connected_node] = connected_node_state.distance;
+                    predecessors[*connected_node] = state.node;
                 }
             }
         }
@@ -0,0 +1,209 @@
+use std::cmp::{max, min, Ordering};
+use crate::bigint::BigInt;
+use crate::rational::Rational;
+
+pub type Point = (i64, i64);
+
+// twice the area of a simple polygon (shoelace formula), which is always an integer for lattice points
+pub fn doubled_area(vertices: &[Point]) -> i64 {
+    signed_doubled_area(vertices).abs()
+}
+
+// positive if the vertices go counterclockwise with y pointing up (clockwise with y pointing down)
+pub fn signed_doubled_area(vertices: &[Point]) -> i64 {
+    let sum = (0..vertices.len())
+        .map(|i| cross(vertices[i], vertices[(i + 1) % vertices.len()]))
+        .sum::<i128>();
+
+    sum as i64
+}
+
+// number of lattice points on the edges of the polygon
+pub fn boundary_points(vertices: &[Point]) -> i64 {
+    (0..vertices.len())
+        .map(|i| {
+            let (x_1, y_1) = vertices[i];
+            let (x_2, y_2) = vertices[(i + 1) % vertices.len()];
+            gcd((x_2 - x_1).abs(), (y_2 - y_1).abs())
+        })
+        .sum()
+}
+
+// number of lattice points strictly inside the polygon, using pick's theorem: A = I + B / 2 - 1
+pub fn interior_points(vertices: &[Point]) -> i64 {
+    (doubled_area(vertices) - boundary_points(vertices) + 2) / 2
+}
+
+#[derive(Copy, Clone, Eq, PartialEq, Debug)]
+pub enum PointLocation {
+    Inside,
+    OnBoundary,
+    Outside,
+}
+
+// even-odd rule, casting a ray from the point towards positive x
+#[allow(dead_code)]
+pub fn locate_point(vertices: &[Point], (x, y): Point) -> PointLocation {
+    let mut inside = false;
+
+    for i in 0..vertices.len() {
+        let (x_1, y_1) = vertices[i];
+        let (x_2, y_2) = vertices[(i + 1) % vertices.len()];
+
+        let on_line = cross((x_2 - x_1, y_2 - y_1), (x - x_1, y - y_1)) == 0;
+        if on_line && min(x_1, x_2) <= x && x <= max(x_1, x_2) && min(y_1, y_2) <= y && y <= max(y_1, y_2) {
+            return PointLocation::OnBoundary;
+        }
+
+        // half-open in y, so a vertex exactly at the height of the ray is only counted once
+        if (y_1 > y) != (y_2 > y) {
+            // x coordinate of the edge at height y compared to x, without dividing
+            let side = cross((x_2 - x_1, y_2 - y_1), (x - x_1, y - y_1));
+            if (side > 0) == (y_2 > y_1) {
+                inside = !inside;
+            }
+        }
+    }
+
+    match inside {
+        true => PointLocation::Inside,
+        false => PointLocation::Outside,
+    }
+}
+
+// all points origin + t * direction
+#[derive(Copy, Clone, Debug)]
+pub struct Line {
+    pub origin: Point,
+    pub direction: Point,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum Intersection {
+    // a.at(s) == b.at(t)
+    Crossing { s: Rational, t: Rational },
+    // the lines (or points) never meet
+    Parallel,
+    // the lines are the same, so the shapes overlap in more than a single point
+    Coincident,
+    // the lines cross (or coincide), but not within the extent of the rays or segments
+    Disjoint,
+}
+
+// a line without a direction is just its origin, which meets the other line at most once
+pub fn line_intersection(a: &Line, b: &Line) -> Intersection {
+    match (a.direction == (0, 0), b.direction == (0, 0)) {
+        (true, true) if a.origin == b.origin => {
+            return Intersection::Crossing { s: Rational::zero(), t: Rational::zero() };
+        }
+        (true, true) => return Intersection::Parallel,
+        (true, false) => {
+            return match position_on_line(a.origin, b) {
+                Some(t) => Intersection::Crossing { s: Rational::zero(), t },
+                None => Intersection::Parallel,
+            };
+        }
+        (false, true) => {
+            return match position_on_line(b.origin, a) {
+                Some(s) => Intersection::Crossing { s, t: Rational::zero() },
+                None => Intersection::Parallel,
+            };
+        }
+        (false, false) => {}
+    }
+
+    let offset = (b.origin.0 - a.origin.0, b.origin.1 - a.origin.1);
+    let denominator = cross(a.direction, b.direction);
+
+    if denominator == 0 {
+        return match cross(a.direction, offset) {
+            0 => Intersection::Coincident,
+            _ => Intersection::Parallel,
+        };
+    }
+
+    // cramer's rule for a.origin + s * a.direction = b.origin + t * b.direction
+    let denominator = BigInt::from(denominator);
+    Intersection::Crossing {
+        s: Rational::new(BigInt::from(cross(offset, b.direction)), denominator.clone()),
+        t: Rational::new(BigInt::from(cross(offset, a.direction)), denominator),
+    }
+}
+
+// the t with line.at(t) == point, if the point is on the (non-degenerate) line
+fn position_on_line(point: Point, line: &Line) -> Option<Rational> {
+    let offset = (point.0 - line.origin.0, point.1 - line.origin.1);
+
+    match cross(line.direction, offset) {
+        0 => Some(Rational::new(
+            BigInt::from(dot(line.direction, offset)),
+            BigInt::from(dot(line.direction, line.direction)),
+        )),
+        _ => None,
+    }
+}
+
+// rays start at their origin and extend into their direction
+pub fn ray_intersection(a: &Line, b: &Line) -> Intersection {
+    match line_intersection(a, b) {
+        Intersection::Crossing { s, t } if s.is_negative() || t.is_negative() => Intersection::Disjoint,
+        // collinear rays overlap unless they point away from each other
+        Intersection::Coincident if dot(a.direction, b.direction) < 0 => {
+            match dot(a.direction, (b.origin.0 - a.origin.0, b.origin.1 - a.origin.1)).cmp(&0) {
+                Ordering::Less => Intersection::Disjoint,
+                Ordering::Equal => Intersection::Crossing { s: Rational::zero(), t: Rational::zero() },
+                Ordering::Greater => Intersection::Coincident,
+            }
+        }
+        intersection => intersection,
+    }
+}
+
+// segments go from their origin to origin + direction
+#[allow(dead_code)]
+pub fn segment_intersection(a: &Line, b: &Line) -> Intersection {
+    let one = Rational::from(1);
+
+    match line_intersection(a, b) {
+        Intersection::Crossing { s, t } if s.is_negative() || t.is_negative() || s > one || t > one => Intersection::Disjoint,
+        Intersection::Coincident => {
+            // project the endpoints of b onto a, where a covers 0..=|a|^2
+            let length = dot(a.direction, a.direction);
+            let start = dot(a.direction, (b.origin.0 - a.origin.0, b.origin.1 - a.origin.1));
+            let end = start + dot(a.direction, b.direction);
+
+            match max(min(start, end), 0).cmp(&min(max(start, end), length)) {
+                Ordering::Greater => Intersection::Disjoint,
+                // touching only at an endpoint is a single crossing point
+                Ordering::Equal => {
+                    let point = max(min(start, end), 0);
+                    let s = Rational::new(BigInt::from(point), BigInt::from(length));
+                    let t = match point == start {
+                        true => Rational::zero(),
+                        false => one,
+                    };
+
+                    Intersection::Crossing { s, t }
+                }
+                Ordering::Less => Intersection::Coincident,
+            }
+        }
+        intersection => intersection,
+    }
+}
+
+fn cross((x_1, y_1): Point, (x_2, y_2): Point) -> i128 {
+    x_1 as i128 * y_2 as i128 - y_1 as i128 * x_2 as i128
+}
+
+fn dot((x_1, y_1): Point, (x_2, y_2): Point) -> i128 {
+    x_1 as i128 * x_2 as i128 + y_1 as i128 * y_2 as i128
+}
+
+fn gcd(a: i64, b: i64) -> i64 {
+    if b == 0 {
+        return a;
+    }
+
+    gcd(b, a % b)
+}
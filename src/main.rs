@@ -1,5 +1,13 @@
 extern crate core;
 
+mod animation;
+mod bigint;
+mod bitset;
+mod geometry;
+mod interner;
+mod rational;
+mod simulation;
+
 mod day01;
 mod day02;
 mod day03;
@@ -52,4 +60,23 @@ fn main() {
     // day23::main();
     // day24::main();
     day25::main();
+
+    // day01::debug();
+    // day01::stream();
+    // day02::estimate();
+    // day03::annotate();
+    // day04::report();
+    // day05::table();
+    // day05::lookup();
+    // day06::verify();
+    // day06::explore();
+    // day07::custom();
+    // day07::benchmark();
+    // day07::odds();
+    // day09::analyze();
+    // day10::render();
+    // day11::nearest();
+    // day14::animation();
+    // day16::animation();
+    // day22::animation();
 }
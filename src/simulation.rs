@@ -0,0 +1,131 @@
+// a deterministic, step-based simulation; replaying the same steps from a snapshot must
+// lead to the same state again
+pub trait Simulation: Clone {
+    type Event;
+
+    // advances the simulation by one step and returns what happened during that step,
+    // or None if the simulation has already come to rest
+    fn step(&mut self) -> Option<Vec<Self::Event>>;
+}
+
+pub struct Simulator<S: Simulation> {
+    state: S,
+    current_step: usize,
+    finished: bool,
+    snapshot_interval: usize,
+    // snapshots[i] is the state after i * snapshot_interval steps
+    snapshots: Vec<S>,
+    // every event together with the (1-based) step it happened in
+    events: Vec<(usize, S::Event)>,
+}
+
+#[allow(dead_code)]
+impl<S: Simulation> Simulator<S> {
+    pub fn new(initial_state: S) -> Simulator<S> {
+        Simulator::with_snapshot_interval(initial_state, 1)
+    }
+
+    // snapshots are only kept every `snapshot_interval` steps, states in between are
+    // reconstructed by replaying from the closest snapshot before them
+    pub fn with_snapshot_interval(initial_state: S, snapshot_interval: usize) -> Simulator<S> {
+        assert!(snapshot_interval > 0, "snapshot interval must be positive");
+
+        Simulator {
+            snapshots: vec![initial_state.clone()],
+            state: initial_state,
+            current_step: 0,
+            finished: false,
+            snapshot_interval,
+            events: Vec::new(),
+        }
+    }
+
+    pub fn state(&self) -> &S {
+        &self.state
+    }
+
+    pub fn current_step(&self) -> usize {
+        self.current_step
+    }
+
+    pub fn is_finished(&self) -> bool {
+        self.finished
+    }
+
+    pub fn events(&self) -> &[(usize, S::Event)] {
+        &self.events
+    }
+
+    pub fn events_of_step(&self, step: usize) -> impl Iterator<Item=&S::Event> {
+        let start = self.events.partition_point(|(event_step, _)| *event_step < step);
+
+        self.events[start..].iter()
+            .take_while(move |(event_step, _)| *event_step == step)
+            .map(|(_, event)| event)
+    }
+
+    // returns false if the simulation had already come to rest
+    pub fn step(&mut self) -> bool {
+        if self.finished {
+            return false;
+        }
+
+        let Some(events) = self.state.step() else {
+            self.finished = true;
+            return false;
+        };
+
+        self.current_step += 1;
+        self.events.extend(events.into_iter().map(|event| (self.current_step, event)));
+
+        if self.current_step.is_multiple_of(self.snapshot_interval) {
+            self.snapshots.push(self.state.clone());
+        }
+
+        true
+    }
+
+    // steps until the predicate holds for the current state; returns false if the
+    // simulation came to rest before that
+    pub fn run_until<P: FnMut(&S) -> bool>(&mut self, mut predicate: P) -> bool {
+        while !predicate(&self.state) {
+            if !self.step() {
+                return false;
+            }
+        }
+
+        true
+    }
+
+    pub fn run(&mut self) {
+        while self.step() {}
+    }
+
+    // the state after the given number of steps, as long as that step was already reached
+    pub fn snapshot(&self, step: usize) -> Option<S> {
+        if step > self.current_step {
+            return None;
+        }
+
+        let mut state = self.snapshots[step / self.snapshot_interval].clone();
+
+        for _ in 0..step % self.snapshot_interval {
+            state.step();
+        }
+
+        Some(state)
+    }
+
+    // goes back to the given step, forgetting everything that happened after it
+    pub fn rewind(&mut self, step: usize) {
+        let Some(state) = self.snapshot(step) else {
+            return;
+        };
+
+        self.state = state;
+        self.current_step = step;
+        self.finished = false;
+        self.snapshots.truncate(step / self.snapshot_interval + 1);
+        self.events.retain(|(event_step, _)| *event_step <= step);
+    }
+}